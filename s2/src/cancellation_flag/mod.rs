@@ -8,3 +8,6 @@ pub type NeverCancelled = r#const::Const<false>;
 
 mod atomic;
 pub use atomic::Atomic;
+
+mod rate_limited;
+pub(crate) use rate_limited::RateLimitedCancellationFlag;
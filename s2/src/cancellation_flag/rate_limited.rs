@@ -0,0 +1,35 @@
+use super::CancellationFlag;
+
+#[derive(Debug)]
+pub struct RateLimitedCancellationFlag<'a, const RATE: u64, C>
+where
+    C: CancellationFlag,
+{
+    count: u64,
+    cancellation_flag: &'a C,
+}
+
+impl<'a, const RATE: u64, C> RateLimitedCancellationFlag<'a, RATE, C>
+where
+    C: CancellationFlag,
+{
+    pub fn new(cancellation_flag: &'a C) -> Self {
+        Self {
+            count: 0,
+            cancellation_flag,
+        }
+    }
+
+    pub fn cancelled(&mut self) -> bool {
+        self.count += 1;
+        self.count.is_multiple_of(RATE) && self.cancellation_flag.cancelled()
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn never_checked(&self) -> bool {
+        self.count() == 0
+    }
+}
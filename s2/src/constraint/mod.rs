@@ -0,0 +1,165 @@
+use crate::grid::{BoxLayout, CellIdx, ColIdx, Digit, RowIdx, DIM};
+use std::fmt::Debug;
+use std::ops::Index;
+use strum::IntoEnumIterator;
+
+mod rules;
+
+mod variants;
+pub use variants::{Rules, Variants};
+
+/// Rule on the digits of some cells. Rows, columns, boxes and the variants of `Variants` are
+/// rules too, and rules the crate does not know about go through the same trait.
+///
+/// Candidates are given as one mask per cell in row-major order, bit `d` standing for digit
+/// `d + 1`, that is `1 << u8::from(digit)`. Set cells hold their digit only.
+pub trait Constraint: Debug + Send + Sync {
+    /// Cells the rule is about.
+    fn cells(&self) -> &[CellIdx];
+
+    /// Removes the candidates of `cells` the rule rules out given the other ones. Removing too
+    /// few is fine, removing one that some solution needs is not.
+    fn prune(&self, candidates: &mut [u16; CellIdx::COUNT]);
+
+    /// Whether the digits set in `grid` may still satisfy the rule, unset cells taking any digit.
+    fn validate(&self, grid: &dyn Index<CellIdx, Output = Option<Digit>>) -> bool;
+
+    /// Whether the rule only asks for distinct digits in `cells`, as rows, columns and boxes do.
+    /// Solvers look for naked sets among the cells of such rules.
+    fn is_unit(&self) -> bool {
+        false
+    }
+}
+
+impl<T> Constraint for &T
+where
+    T: Constraint + ?Sized,
+{
+    fn cells(&self) -> &[CellIdx] {
+        (**self).cells()
+    }
+
+    fn prune(&self, candidates: &mut [u16; CellIdx::COUNT]) {
+        (**self).prune(candidates)
+    }
+
+    fn validate(&self, grid: &dyn Index<CellIdx, Output = Option<Digit>>) -> bool {
+        (**self).validate(grid)
+    }
+
+    fn is_unit(&self) -> bool {
+        (**self).is_unit()
+    }
+}
+
+/// Cells holding distinct digits, as rows, columns and boxes do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distinct {
+    cells: Vec<CellIdx>,
+}
+
+impl Distinct {
+    pub fn new(cells: Vec<CellIdx>) -> Self {
+        Self { cells }
+    }
+}
+
+impl Constraint for Distinct {
+    fn cells(&self) -> &[CellIdx] {
+        &self.cells
+    }
+
+    fn prune(&self, candidates: &mut [u16; CellIdx::COUNT]) {
+        for idx in &self.cells {
+            let single = candidates[idx.row_major()];
+            if single.count_ones() != 1 {
+                continue;
+            }
+            self.cells
+                .iter()
+                .filter(|other| *other != idx)
+                .for_each(|other| candidates[other.row_major()] &= !single);
+        }
+    }
+
+    fn validate(&self, grid: &dyn Index<CellIdx, Output = Option<Digit>>) -> bool {
+        let mut seen = 0u16;
+        self.cells.iter().filter_map(|idx| grid[*idx]).all(|value| {
+            let bit = 1 << u8::from(value);
+            let repeated = seen & bit != 0;
+            seen |= bit;
+            !repeated
+        })
+    }
+
+    fn is_unit(&self) -> bool {
+        true
+    }
+}
+
+/// Rows and columns, then the boxes of `layout` unless `boxes` is false.
+fn units(layout: &BoxLayout, boxes: bool) -> impl Iterator<Item = Distinct> + '_ {
+    let rows = RowIdx::iter().map(|i| {
+        ColIdx::iter()
+            .map(|j| (i, j).into())
+            .collect::<Vec<CellIdx>>()
+    });
+    let cols = ColIdx::iter().map(|j| {
+        RowIdx::iter()
+            .map(|i| (i, j).into())
+            .collect::<Vec<CellIdx>>()
+    });
+    let boxes = (0..DIM)
+        .filter(move |_| boxes)
+        .map(|box_| layout.cells(box_).collect::<Vec<_>>());
+    rows.chain(cols).chain(boxes).map(Distinct::new)
+}
+
+/// Rows, columns and boxes of classic sudoku.
+pub fn classic() -> Vec<Box<dyn Constraint>> {
+    units(&BoxLayout::classic(), true)
+        .map(|unit| Box::new(unit) as Box<dyn Constraint>)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{classic, Constraint, Distinct};
+    use crate::grid::{ArrGridRowMajor, CellIdx, Digit};
+
+    #[test]
+    fn test_distinct() {
+        let idx = |x| CellIdx::try_of_row_major(x).unwrap();
+        let distinct = Distinct::new(vec![idx(0), idx(1), idx(2)]);
+        let mut candidates = [0b1_1111_1111; CellIdx::COUNT];
+        candidates[0] = 0b100;
+        candidates[1] = 0b110;
+        distinct.prune(&mut candidates);
+        assert_eq!(
+            [0b100, 0b010, 0b1_1111_1001, 0b1_1111_1111],
+            candidates[..4]
+        );
+
+        let mut grid = ArrGridRowMajor::new();
+        grid[idx(0)] = Some(Digit::D3);
+        grid[idx(3)] = Some(Digit::D3);
+        assert!(distinct.validate(&grid));
+        grid[idx(2)] = Some(Digit::D3);
+        assert!(!distinct.validate(&grid));
+    }
+
+    #[test]
+    fn test_classic() {
+        let units = classic();
+        assert_eq!(27, units.len());
+        assert!(units.iter().all(|unit| unit.cells().len() == 9));
+        assert_eq!(
+            vec![0, 1, 2, 9, 10, 11, 18, 19, 20],
+            units[18]
+                .cells()
+                .iter()
+                .map(CellIdx::row_major)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use super::Constraint;
+use crate::grid::{Cage, CellIdx, ChessMove, Digit, Line, OutsideClue, Pair, Parity, DIM};
+use std::ops::Index;
+
+/// Digits from 1 to 9 of a single-candidate mask, `None` for any other mask.
+fn single(mask: u16) -> Option<usize> {
+    (mask.count_ones() == 1).then_some(mask.trailing_zeros() as usize + 1)
+}
+
+/// Killer cage: distinct digits adding up to the cage sum.
+#[derive(Debug)]
+pub(super) struct Killer {
+    cells: Vec<CellIdx>,
+    combinations: Vec<u16>,
+}
+
+impl Killer {
+    pub fn new(cage: &Cage) -> Self {
+        Self {
+            cells: cage.cells().to_vec(),
+            combinations: cage.combination_masks().collect(),
+        }
+    }
+}
+
+impl Constraint for Killer {
+    fn cells(&self) -> &[CellIdx] {
+        &self.cells
+    }
+
+    fn prune(&self, candidates: &mut [u16; CellIdx::COUNT]) {
+        let masks = self
+            .cells
+            .iter()
+            .map(|idx| candidates[idx.row_major()])
+            .collect::<Vec<_>>();
+        let used = masks
+            .iter()
+            .filter(|mask| mask.count_ones() == 1)
+            .fold(0, |acc, mask| acc | mask);
+        let allowed = self
+            .combinations
+            .iter()
+            .filter(|combination| *combination & used == used)
+            .filter(|combination| masks.iter().all(|mask| *combination & mask != 0))
+            .fold(0, |acc, combination| acc | combination);
+        for (idx, mask) in self.cells.iter().zip(masks) {
+            candidates[idx.row_major()] &= match mask.count_ones() {
+                1 => allowed,
+                _ => allowed & !used,
+            };
+        }
+    }
+
+    fn validate(&self, grid: &dyn Index<CellIdx, Output = Option<Digit>>) -> bool {
+        let mut used = 0u16;
+        for value in self.cells.iter().filter_map(|idx| grid[*idx]) {
+            let value = 1 << u8::from(value);
+            if used & value != 0 {
+                return false;
+            }
+            used |= value;
+        }
+        self.combinations
+            .iter()
+            .any(|combination| combination & used == used)
+    }
+}
+
+/// No equal digits a move of any of the chess moves apart.
+#[derive(Debug)]
+pub(super) struct AntiChess {
+    cells: Vec<CellIdx>,
+    /// Cells a move away from each cell in row-major order.
+    peers: Vec<Vec<usize>>,
+}
+
+impl AntiChess {
+    pub fn new(moves: &[ChessMove]) -> Self {
+        Self {
+            cells: CellIdx::iter_row_wise().collect(),
+            peers: CellIdx::iter_row_wise()
+                .map(|idx| {
+                    let mut peers = moves
+                        .iter()
+                        .flat_map(|m| m.peers(idx))
+                        .map(|idx| idx.row_major())
+                        .collect::<Vec<_>>();
+                    peers.sort_unstable();
+                    peers.dedup();
+                    peers
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Constraint for AntiChess {
+    fn cells(&self) -> &[CellIdx] {
+        &self.cells
+    }
+
+    fn prune(&self, candidates: &mut [u16; CellIdx::COUNT]) {
+        for (i, peers) in self.peers.iter().enumerate() {
+            let mask = candidates[i];
+            if mask.count_ones() == 1 {
+                peers.iter().for_each(|peer| candidates[*peer] &= !mask);
+            }
+        }
+    }
+
+    fn validate(&self, grid: &dyn Index<CellIdx, Output = Option<Digit>>) -> bool {
+        self.cells.iter().zip(&self.peers).all(|(idx, peers)| {
+            grid[*idx].is_none_or(|value| {
+                peers
+                    .iter()
+                    .all(|peer| grid[CellIdx::try_of_row_major(*peer).unwrap()] != Some(value))
+            })
+        })
+    }
+}
+
+impl Constraint for Line {
+    fn cells(&self) -> &[CellIdx] {
+        Line::cells(self)
+    }
+
+    fn prune(&self, candidates: &mut [u16; CellIdx::COUNT]) {
+        for (pos, idx) in Line::cells(self).iter().enumerate() {
+            let allowed = self.allowed(pos, |idx| candidates[idx.row_major()]);
+            candidates[idx.row_major()] &= allowed;
+        }
+    }
+
+    fn validate(&self, grid: &dyn Index<CellIdx, Output = Option<Digit>>) -> bool {
+        let candidates_of = |idx: CellIdx| grid[idx].map_or(0x1ff, |value| 1 << u8::from(value));
+        Line::cells(self).iter().enumerate().all(|(pos, idx)| {
+            grid[*idx]
+                .is_none_or(|value| self.allowed(pos, candidates_of) & (1 << u8::from(value)) != 0)
+        })
+    }
+}
+
+/// Pair of cells whose digits satisfy its relation.
+#[derive(Debug)]
+pub(super) struct Related {
+    cells: [CellIdx; 2],
+    /// Digits each cell can take given the digit of the other cell, from 1 to 9, or 0 if unset.
+    allowed: [[u16; DIM + 1]; 2],
+}
+
+impl Related {
+    pub fn new(pair: &Pair) -> Self {
+        let relation = pair.relation();
+        Self {
+            cells: [pair.cells()[0], pair.cells()[1]],
+            allowed: std::array::from_fn(|pos| {
+                std::array::from_fn(|value| {
+                    relation.allowed(pos, (value != 0).then_some(value as u8))
+                })
+            }),
+        }
+    }
+}
+
+impl Constraint for Related {
+    fn cells(&self) -> &[CellIdx] {
+        &self.cells
+    }
+
+    fn prune(&self, candidates: &mut [u16; CellIdx::COUNT]) {
+        for pos in 0..2 {
+            let other = candidates[self.cells[1 - pos].row_major()];
+            let allowed = (1..=DIM)
+                .filter(|value| other & (1 << (value - 1)) != 0)
+                .fold(0, |acc, value| acc | self.allowed[pos][value]);
+            candidates[self.cells[pos].row_major()] &= allowed;
+        }
+    }
+
+    fn validate(&self, grid: &dyn Index<CellIdx, Output = Option<Digit>>) -> bool {
+        let [a, b] = self
+            .cells
+            .map(|idx| grid[idx].map(|value| u8::from(value) + 1));
+        [(0, a, b), (1, b, a)]
+            .into_iter()
+            .all(|(pos, value, other)| {
+                value.is_none_or(|value| {
+                    self.allowed[pos][usize::from(other.unwrap_or(0))] & (1 << (value - 1)) != 0
+                })
+            })
+    }
+}
+
+/// Outside clue, as every line of its row or column satisfying it.
+#[derive(Debug)]
+pub(super) struct Clue {
+    cells: [CellIdx; DIM],
+    /// Every line of digits from 1 to 9 satisfying the clue, in reading order.
+    completions: Vec<[u8; DIM]>,
+    /// Indices of the completions with each digit, from 1 to 9, at each position.
+    by_cell: [[Vec<u32>; DIM]; DIM],
+    /// Digits each position takes in any completion.
+    all_allowed: [u16; DIM],
+}
+
+impl Clue {
+    pub fn new(clue: &OutsideClue) -> Self {
+        let mut completions = Vec::new();
+        clue.search(&[None; DIM], &mut |line| {
+            completions.push(*line);
+            true
+        });
+        let mut by_cell: [[Vec<u32>; DIM]; DIM] = Default::default();
+        let mut all_allowed = [0; DIM];
+        for (i, line) in completions.iter().enumerate() {
+            for (pos, value) in line.iter().enumerate() {
+                by_cell[pos][usize::from(*value - 1)].push(i as u32);
+                all_allowed[pos] |= 1 << (value - 1);
+            }
+        }
+        Self {
+            cells: clue.cells(),
+            completions,
+            by_cell,
+            all_allowed,
+        }
+    }
+
+    /// Completions agreeing with `masks`, one per position, scanning only the ones with the digit
+    /// of the fixed position having the fewest of them. `None` if no position is fixed.
+    fn matching<'a>(
+        &'a self,
+        masks: &'a [u16; DIM],
+    ) -> Option<impl Iterator<Item = &'a [u8; DIM]>> {
+        let bucket = masks
+            .iter()
+            .enumerate()
+            .filter_map(|(pos, mask)| Some(&self.by_cell[pos][single(*mask)? - 1]))
+            .min_by_key(|bucket| bucket.len())?;
+        Some(
+            bucket
+                .iter()
+                .map(|i| &self.completions[*i as usize])
+                .filter(move |line| {
+                    line.iter()
+                        .zip(masks)
+                        .all(|(value, mask)| mask & (1 << (value - 1)) != 0)
+                }),
+        )
+    }
+}
+
+impl Constraint for Clue {
+    fn cells(&self) -> &[CellIdx] {
+        &self.cells
+    }
+
+    fn prune(&self, candidates: &mut [u16; CellIdx::COUNT]) {
+        let masks = self.cells.map(|idx| candidates[idx.row_major()]);
+        let allowed = match self.matching(&masks) {
+            Some(lines) => lines.fold([0; DIM], |mut acc, line| {
+                acc.iter_mut()
+                    .zip(line)
+                    .for_each(|(allowed, value)| *allowed |= 1 << (value - 1));
+                acc
+            }),
+            None => self.all_allowed,
+        };
+        for (idx, allowed) in self.cells.iter().zip(allowed) {
+            candidates[idx.row_major()] &= allowed;
+        }
+    }
+
+    fn validate(&self, grid: &dyn Index<CellIdx, Output = Option<Digit>>) -> bool {
+        let masks = self
+            .cells
+            .map(|idx| grid[idx].map_or(0x1ff, |value| 1 << u8::from(value)));
+        match self.matching(&masks) {
+            Some(mut lines) => lines.next().is_some(),
+            None => !self.completions.is_empty(),
+        }
+    }
+}
+
+/// Shaded cell holding a digit of its parity.
+#[derive(Debug)]
+pub(super) struct Shaded {
+    cells: [CellIdx; 1],
+    parity: Parity,
+}
+
+impl Shaded {
+    pub fn new(idx: CellIdx, parity: Parity) -> Self {
+        Self {
+            cells: [idx],
+            parity,
+        }
+    }
+}
+
+impl Constraint for Shaded {
+    fn cells(&self) -> &[CellIdx] {
+        &self.cells
+    }
+
+    fn prune(&self, candidates: &mut [u16; CellIdx::COUNT]) {
+        candidates[self.cells[0].row_major()] &= self.parity.mask();
+    }
+
+    fn validate(&self, grid: &dyn Index<CellIdx, Output = Option<Digit>>) -> bool {
+        grid[self.cells[0]].is_none_or(|value| self.parity.holds(value))
+    }
+}
@@ -0,0 +1,122 @@
+use super::rules::{AntiChess, Clue, Killer, Related, Shaded};
+use super::{units, Constraint, Distinct};
+use crate::grid::{
+    BoxLayout, Cage, CellIdx, ChessMove, Digit, Line, OutsideClue, Pair, Parity, Region, DIM,
+};
+use std::ops::Index;
+use std::sync::OnceLock;
+
+/// Rules of a puzzle: rows, columns and boxes, and whichever variants are given on top of them.
+/// The default is classic sudoku.
+#[derive(Debug, Default)]
+pub struct Variants {
+    /// Boxes replacing the 3x3 ones, as in jigsaw sudoku.
+    pub layout: BoxLayout,
+    /// Killer cages: distinct digits adding up to the cage sum.
+    pub cages: Vec<Cage>,
+    /// Extra regions holding every digit once, as rows, columns and boxes do.
+    pub regions: Vec<Region>,
+    /// Moves no two equal digits may be apart, as in anti-knight sudoku.
+    pub moves: Vec<ChessMove>,
+    /// Thermometers, arrows and palindromes.
+    pub lines: Vec<Line>,
+    /// Related digits, as in Kropki, XV or greater-than sudoku.
+    pub pairs: Vec<Pair>,
+    /// Sandwich, skyscraper and X-sum clues.
+    pub clues: Vec<OutsideClue>,
+    /// Drops boxes, leaving a Latin square.
+    pub latin: bool,
+    /// Shaded cells holding odd or even digits.
+    pub parities: Vec<(CellIdx, Parity)>,
+    /// Rules the crate does not know about.
+    pub constraints: Vec<Box<dyn Constraint>>,
+}
+
+impl Variants {
+    /// Rules of rows, columns, boxes and every variant but `constraints`, in this order.
+    fn build(&self) -> Vec<Box<dyn Constraint>> {
+        let mut rules: Vec<Box<dyn Constraint>> = units(&self.layout, !self.latin)
+            .chain(
+                self.regions
+                    .iter()
+                    .map(|region| Distinct::new(region.cells().to_vec())),
+            )
+            .map(|unit| Box::new(unit) as _)
+            .collect();
+        rules.extend(
+            self.cages
+                .iter()
+                .map(|cage| Box::new(Killer::new(cage)) as _),
+        );
+        if !self.moves.is_empty() {
+            rules.push(Box::new(AntiChess::new(&self.moves)));
+        }
+        rules.extend(self.lines.iter().map(|line| Box::new(line.clone()) as _));
+        rules.extend(
+            self.pairs
+                .iter()
+                .map(|pair| Box::new(Related::new(pair)) as _),
+        );
+        rules.extend(self.clues.iter().map(|clue| Box::new(Clue::new(clue)) as _));
+        rules.extend(
+            self.parities
+                .iter()
+                .map(|(idx, parity)| Box::new(Shaded::new(*idx, *parity)) as _),
+        );
+        rules
+    }
+}
+
+/// Rules of `Variants`, built the first time they are needed and reused from then on. Building
+/// them enumerates every line satisfying each outside clue, so checking or solving many grids
+/// under the same variants should go through one `Rules`.
+#[derive(Debug, Default)]
+pub struct Rules {
+    variants: Variants,
+    /// Rows, columns, boxes and every variant but `constraints`, in this order.
+    built: OnceLock<Vec<Box<dyn Constraint>>>,
+}
+
+impl From<Variants> for Rules {
+    fn from(variants: Variants) -> Self {
+        Self {
+            variants,
+            built: OnceLock::new(),
+        }
+    }
+}
+
+impl Rules {
+    pub fn variants(&self) -> &Variants {
+        &self.variants
+    }
+
+    /// Variants to change, dropping the rules built from them so far.
+    pub(crate) fn variants_mut(&mut self) -> &mut Variants {
+        self.built.take();
+        &mut self.variants
+    }
+
+    fn built(&self) -> &[Box<dyn Constraint>] {
+        self.built.get_or_init(|| self.variants.build())
+    }
+
+    /// Every rule of the puzzle, rows, columns and boxes first and `constraints` last.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Constraint> {
+        self.built()
+            .iter()
+            .chain(&self.variants.constraints)
+            .map(|rule| &**rule)
+    }
+
+    /// Rules other than rows, columns and boxes, which solvers keep track of on their own.
+    pub(crate) fn extra(&self) -> impl Iterator<Item = &dyn Constraint> {
+        let classic = if self.variants.latin { 2 } else { 3 } * DIM;
+        self.iter().skip(classic)
+    }
+
+    /// Whether the digits set in `grid` may still satisfy every rule.
+    pub fn validate(&self, grid: &dyn Index<CellIdx, Output = Option<Digit>>) -> bool {
+        self.iter().all(|rule| rule.validate(grid))
+    }
+}
@@ -0,0 +1,237 @@
+use super::WriteFormatter;
+use crate::grid::{CellIdx, Grid, DIM};
+use std::io::Write;
+
+/// Characters `BoxDrawing` draws borders with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Borders {
+    /// `+`, `-` and `|`.
+    #[default]
+    Ascii,
+    /// Box-drawing characters such as `┼`, `─` and `│`.
+    Unicode,
+}
+
+impl Borders {
+    /// Corners and crossings from top-left to bottom-right, then the horizontal and the vertical
+    /// line.
+    fn chars(self) -> [char; 11] {
+        match self {
+            Self::Ascii => ['+', '+', '+', '+', '+', '+', '+', '+', '+', '-', '|'],
+            Self::Unicode => ['┌', '┬', '┐', '├', '┼', '┤', '└', '┴', '┘', '─', '│'],
+        }
+    }
+}
+
+/// Text format for people rather than programs, with borders around boxes. With candidates,
+/// each cell is drawn as a 3x3 mini-grid of the digits its row, column and box still allow, and
+/// set cells show their digit in the middle. Only writes.
+///
+/// ```text
+/// +-------+-------+-------+
+/// | 5 3 . | . 7 . | . . . |
+/// ```
+#[derive(Debug)]
+pub struct BoxDrawing {
+    borders: Borders,
+    empty_cell: char,
+    candidates: bool,
+}
+
+impl Default for BoxDrawing {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+impl BoxDrawing {
+    pub fn new(borders: Option<Borders>, empty_cell: Option<char>) -> Self {
+        Self {
+            borders: borders.unwrap_or_default(),
+            empty_cell: empty_cell.unwrap_or('.'),
+            candidates: false,
+        }
+    }
+
+    /// Draws candidates of empty cells, `empty_cell` standing for the digits ruled out.
+    pub fn with_candidates(mut self) -> Self {
+        self.candidates = true;
+        self
+    }
+
+    /// Digits the row, column and box of `idx` leave for it, one bit per digit.
+    fn candidates<G>(grid: &G, idx: CellIdx) -> u16
+    where
+        G: Grid + ?Sized,
+    {
+        let used = CellIdx::iter_row_wise()
+            .filter(|other| {
+                other.row == idx.row || other.col == idx.col || other.box_() == idx.box_()
+            })
+            .filter_map(|other| grid[other])
+            .fold(0, |acc, value| acc | 1 << u8::from(value));
+        !used & ((1 << DIM) - 1)
+    }
+
+    /// Line `k` of the cell at `idx`, out of 3 with candidates and of 1 otherwise.
+    fn cell<G>(&self, grid: &G, idx: CellIdx, k: usize, line: &mut String)
+    where
+        G: Grid + ?Sized,
+    {
+        let digit = |value: u8| char::from(b'1' + value);
+        match (grid[idx], self.candidates) {
+            (Some(value), false) => line.push(digit(value.into())),
+            (None, false) => line.push(self.empty_cell),
+            (Some(value), true) if k == 1 => {
+                line.extend([' ', digit(value.into()), ' ']);
+            }
+            (Some(_), true) => line.push_str("   "),
+            (None, true) => {
+                let candidates = Self::candidates(grid, idx);
+                line.extend((3 * k as u8..3 * k as u8 + 3).map(|value| {
+                    match candidates & (1 << value) {
+                        0 => self.empty_cell,
+                        _ => digit(value),
+                    }
+                }));
+            }
+        }
+    }
+
+    fn to_string<G>(&self, grid: &G) -> String
+    where
+        G: Grid + ?Sized,
+    {
+        let [tl, tm, tr, ml, mm, mr, bl, bm, br, h, v] = self.borders.chars();
+        let (width, height) = match self.candidates {
+            true => (3, 3),
+            false => (1, 1),
+        };
+        let inner = 3 * width + 4;
+        let rule = |left: char, mid: char, right: char| {
+            let h = h.to_string().repeat(inner);
+            format!("{left}{h}{mid}{h}{mid}{h}{right}")
+        };
+        let blank = format!("{v}{0}{v}{0}{v}{0}{v}", " ".repeat(inner));
+        let mut lines = vec![rule(tl, tm, tr)];
+        let cells = CellIdx::iter_row_wise().collect::<Vec<_>>();
+        for (i, row) in cells.chunks(DIM).enumerate() {
+            if self.candidates && i % 3 != 0 {
+                lines.push(blank.clone());
+            }
+            for k in 0..height {
+                let mut line = String::new();
+                for (j, idx) in row.iter().enumerate() {
+                    match j {
+                        0 => line.extend([v, ' ']),
+                        _ if j % 3 == 0 => line.extend([' ', v, ' ']),
+                        _ => line.push(' '),
+                    }
+                    self.cell(grid, *idx, k, &mut line);
+                }
+                line.push(' ');
+                line.push(v);
+                lines.push(line);
+            }
+            match i {
+                8 => lines.push(rule(bl, bm, br)),
+                _ if i % 3 == 2 => lines.push(rule(ml, mm, mr)),
+                _ => (),
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+impl WriteFormatter for BoxDrawing {
+    fn write<G, W>(&self, grid: &G, writer: &mut W) -> std::io::Result<usize>
+    where
+        G: Grid + ?Sized,
+        W: Write,
+    {
+        let s = self.to_string(grid);
+        writer.write_all(s.as_bytes())?;
+        Ok(s.len())
+    }
+}
+
+#[cfg(test)]
+mod box_drawing_test {
+    use super::{Borders, BoxDrawing};
+    use crate::format::{read_from_string, write_string, RowMajorAscii};
+    use crate::grid::ArrGridRowMajor;
+
+    fn grid() -> ArrGridRowMajor {
+        read_from_string(
+            &RowMajorAscii::default(),
+            r#"
+53__7____
+6__195___
+_98____6_
+8___6___3
+4__8_3__1
+7___2___6
+_6____28_
+___419__5
+____8__79
+"#
+            .trim(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ascii() {
+        let expected = r#"
++-------+-------+-------+
+| 5 3 . | . 7 . | . . . |
+| 6 . . | 1 9 5 | . . . |
+| . 9 8 | . . . | . 6 . |
++-------+-------+-------+
+| 8 . . | . 6 . | . . 3 |
+| 4 . . | 8 . 3 | . . 1 |
+| 7 . . | . 2 . | . . 6 |
++-------+-------+-------+
+| . 6 . | . . . | 2 8 . |
+| . . . | 4 1 9 | . . 5 |
+| . . . | . 8 . | . 7 9 |
++-------+-------+-------+"#
+            .trim_start();
+        assert_eq!(expected, write_string(&BoxDrawing::default(), &grid()));
+        assert_eq!(expected, format!("{:?}", grid()));
+    }
+
+    #[test]
+    fn test_unicode() {
+        let s = write_string(&BoxDrawing::new(Some(Borders::Unicode), Some(' ')), &grid());
+        let lines = s.lines().collect::<Vec<_>>();
+        assert_eq!(13, lines.len());
+        assert_eq!("┌───────┬───────┬───────┐", lines[0]);
+        assert_eq!("│ 5 3   │   7   │       │", lines[1]);
+        assert_eq!("├───────┼───────┼───────┤", lines[4]);
+        assert_eq!("└───────┴───────┴───────┘", lines[12]);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let s = write_string(&BoxDrawing::default().with_candidates(), &grid());
+        let lines = s.lines().collect::<Vec<_>>();
+        assert_eq!(4 + 9 * 3 + 6, lines.len());
+        // The third cell may still be 1, 2 or 4, and the fourth one 2 or 6.
+        let expected = r#"
++-------------+-------------+-------------+
+|         12. | .2.     .2. | 1.. 12. .2. |
+|  5   3  4.. | ..6  7  4.6 | 4.. 4.. 4.. |
+|         ... | ...     .8. | .89 ..9 .8. |
+|             |             |             |
+|     .2. .2. |             | ..3 .23 .2. |
+|  6  4.. 4.. |  1   9   5  | 4.. 4.. 4.. |
+|     7.. 7.. |             | 78. ... 78. |"#
+            .trim_start();
+        assert_eq!(expected, lines[..8].join("\n"));
+        assert_eq!(
+            "+-------------+-------------+-------------+",
+            lines[lines.len() - 1]
+        );
+    }
+}
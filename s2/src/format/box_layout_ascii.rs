@@ -0,0 +1,157 @@
+use crate::grid::{BoxLayout, BoxLayoutError, CellIdx, ColIdx, DIM};
+use std::io::{Cursor, Read, Write};
+use std::slice;
+use strum::EnumCount;
+use thiserror::Error;
+
+const DEFAULT_SYMBOLS: &str = "ABCDEFGHI";
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum BoxLayoutAsciiReadError {
+    #[error("Reading a box layout fails")]
+    Syntax,
+    #[error("more than 9 box symbols")]
+    TooManyBoxes,
+    #[error(transparent)]
+    Layout(#[from] BoxLayoutError),
+}
+
+/// Row-major text format for box layouts, one symbol per cell. Any 9 symbols can be read, boxes
+/// being numbered in order of first appearance. Box `i` is written as the `i`-th symbol.
+#[derive(Debug)]
+pub struct BoxLayoutAscii {
+    symbols: Vec<u8>,
+    row_sep: Option<u8>,
+}
+
+impl Default for BoxLayoutAscii {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+impl BoxLayoutAscii {
+    pub fn new(symbols: Option<&str>, row_sep: Option<Option<char>>) -> Self {
+        let symbols = symbols.unwrap_or(DEFAULT_SYMBOLS).as_bytes().to_vec();
+        let row_sep: Option<u8> = row_sep.unwrap_or(Some('\n')).map(|x| x.try_into().unwrap());
+        assert!(symbols.len() == DIM && symbols.iter().all(u8::is_ascii_graphic));
+        Self { symbols, row_sep }
+    }
+
+    pub fn read<R>(&self, reader: &mut R) -> Result<BoxLayout, BoxLayoutAsciiReadError>
+    where
+        R: Read,
+    {
+        let mut symbols = Vec::with_capacity(DIM);
+        let mut boxes = [0; CellIdx::COUNT];
+        let mut row_major_idx = 0;
+        let mut row_sep_expected = false;
+        while row_major_idx < CellIdx::COUNT {
+            let mut c = 0;
+            reader
+                .read_exact(slice::from_mut(&mut c))
+                .map_err(|_| BoxLayoutAsciiReadError::Syntax)?;
+            if row_sep_expected && self.row_sep == Some(c) {
+                row_sep_expected = false;
+            } else if c.is_ascii_whitespace() {
+                continue;
+            } else if row_sep_expected || !c.is_ascii_graphic() {
+                return Err(BoxLayoutAsciiReadError::Syntax);
+            } else {
+                let box_ = match symbols.iter().position(|x| *x == c) {
+                    Some(box_) => box_,
+                    None if symbols.len() < DIM => {
+                        symbols.push(c);
+                        symbols.len() - 1
+                    }
+                    None => return Err(BoxLayoutAsciiReadError::TooManyBoxes),
+                };
+                boxes[row_major_idx] = box_ as u8;
+                row_major_idx += 1;
+                row_sep_expected = self.row_sep.is_some()
+                    && row_major_idx < CellIdx::COUNT
+                    && row_major_idx % ColIdx::COUNT == 0;
+            }
+        }
+        Ok(BoxLayout::new(boxes)?)
+    }
+
+    pub fn read_from_string(&self, s: &str) -> Result<BoxLayout, BoxLayoutAsciiReadError> {
+        self.read(&mut Cursor::new(s.as_bytes()))
+    }
+
+    pub fn write<W>(&self, layout: &BoxLayout, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: Write,
+    {
+        CellIdx::iter_row_wise().try_fold(0, |res, idx| {
+            let cell = writer.write(slice::from_ref(&self.symbols[layout.box_of(idx)]))?;
+            let row_sep = match self.row_sep {
+                Some(x) if idx.row_major() + 1 != CellIdx::COUNT && idx.col == ColIdx::Col8 => {
+                    writer.write(slice::from_ref(&x))?
+                }
+                Some(_) | None => 0,
+            };
+            Ok(res + cell + row_sep)
+        })
+    }
+
+    pub fn write_string(&self, layout: &BoxLayout) -> String {
+        let mut cursor = Cursor::new(Vec::with_capacity(CellIdx::COUNT + DIM - 1));
+        self.write(layout, &mut cursor).unwrap();
+        String::from_utf8(cursor.into_inner()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod box_layout_ascii_test {
+    use super::{BoxLayoutAscii, BoxLayoutAsciiReadError};
+    use crate::grid::{BoxLayout, BoxLayoutError, CellIdx};
+
+    #[test]
+    fn test_roundtrip() {
+        let expected = r#"
+AAABBBCCC
+AAAABBCCC
+AADBBBCCC
+DDDEBEFFF
+DDEEEEFFF
+DDDEEEFFF
+GGGHHHIII
+GGGHHHIII
+GGGHHHIII
+"#
+        .trim();
+        let f = BoxLayoutAscii::default();
+        let layout = f.read_from_string(expected).unwrap();
+        assert_eq!(3, layout.box_of(CellIdx::try_of_row_major(20).unwrap()));
+        assert_eq!(expected, f.write_string(&layout));
+
+        // Symbols are only labels.
+        let relabeled = expected.replace('A', "#").replace('I', "A");
+        assert_eq!(layout, f.read_from_string(&relabeled).unwrap());
+
+        let f = BoxLayoutAscii::new(Some("123456789"), Some(None));
+        let s = f.write_string(&BoxLayout::classic());
+        assert_eq!(format!("{0}{0}{0}", "111222333"), s[..27]);
+        assert_eq!(BoxLayout::classic(), f.read_from_string(&s).unwrap());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let f = BoxLayoutAscii::default();
+        assert_eq!(
+            Err(BoxLayoutAsciiReadError::Syntax),
+            f.read_from_string("AAABBBCCC")
+        );
+        assert_eq!(
+            Err(BoxLayoutAsciiReadError::TooManyBoxes),
+            f.read_from_string("ABCDEFGHI\nJ")
+        );
+        let s = f.write_string(&BoxLayout::classic()).replacen('A', "B", 1);
+        assert_eq!(
+            Err(BoxLayoutAsciiReadError::Layout(BoxLayoutError::Size)),
+            f.read_from_string(&s)
+        );
+    }
+}
@@ -0,0 +1,315 @@
+use crate::grid::{CompositeGrid, CompositeLayout, CompositeLayoutError, Digit, SizedCellIdx};
+use std::io::{Cursor, Read, Write};
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum CompositeLayoutAsciiReadError {
+    #[error("Reading a layout fails")]
+    Io,
+    #[error("line {line} is neither a preset nor `<row> <col>`")]
+    Syntax { line: usize },
+    #[error(transparent)]
+    Layout(#[from] CompositeLayoutError),
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum CompositeAsciiReadError {
+    #[error("Reading a grid fails")]
+    Io,
+    #[error("line {line} does not match the layout")]
+    Syntax { line: usize },
+}
+
+fn preset(name: &str) -> Option<CompositeLayout> {
+    match name.to_ascii_lowercase().as_str() {
+        "samurai" => Some(CompositeLayout::samurai()),
+        "twodoku" => Some(CompositeLayout::twodoku()),
+        "butterfly" => Some(CompositeLayout::butterfly()),
+        _ => None,
+    }
+}
+
+/// Text format for composite layouts: one grid per line as the row and column of its top-left
+/// cell, numbered from 1, such as `7 7`. A single line `samurai`, `twodoku` or `butterfly` stands
+/// for the preset. Blank lines and lines starting with `#` are skipped.
+#[derive(Debug, Default)]
+pub struct CompositeLayoutAscii;
+
+impl CompositeLayoutAscii {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn read<R>(&self, reader: &mut R) -> Result<CompositeLayout, CompositeLayoutAsciiReadError>
+    where
+        R: Read,
+    {
+        let mut s = String::new();
+        reader
+            .read_to_string(&mut s)
+            .map_err(|_| CompositeLayoutAsciiReadError::Io)?;
+        let lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, text)| (i + 1, text.trim()))
+            .filter(|(_, text)| !text.is_empty() && !text.starts_with('#'))
+            .collect::<Vec<_>>();
+        match lines[..] {
+            [(line, text)] if !text.starts_with(|c: char| c.is_ascii_digit()) => {
+                return preset(text).ok_or(CompositeLayoutAsciiReadError::Syntax { line });
+            }
+            _ => (),
+        }
+        let origins = lines
+            .into_iter()
+            .map(|(line, text)| {
+                let syntax = || CompositeLayoutAsciiReadError::Syntax { line };
+                let [row, col] = text
+                    .split_whitespace()
+                    .map(|x| x.parse::<usize>().ok()?.checked_sub(1))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(syntax)?
+                    .try_into()
+                    .map_err(|_| syntax())?;
+                Ok(SizedCellIdx { row, col })
+            })
+            .collect::<Result<Vec<_>, CompositeLayoutAsciiReadError>>()?;
+        Ok(CompositeLayout::new(origins)?)
+    }
+
+    pub fn read_from_string(
+        &self,
+        s: &str,
+    ) -> Result<CompositeLayout, CompositeLayoutAsciiReadError> {
+        self.read(&mut Cursor::new(s.as_bytes()))
+    }
+
+    pub fn write<W>(&self, layout: &CompositeLayout, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: Write,
+    {
+        layout.origins().iter().try_fold(0, |res, origin| {
+            let line = format!("{} {}\n", origin.row + 1, origin.col + 1);
+            writer.write_all(line.as_bytes())?;
+            Ok(res + line.len())
+        })
+    }
+
+    pub fn write_string(&self, layout: &CompositeLayout) -> String {
+        let mut cursor = Cursor::new(Vec::new());
+        self.write(layout, &mut cursor).unwrap();
+        String::from_utf8(cursor.into_inner()).unwrap()
+    }
+}
+
+/// Row-major text format for composite puzzles, one line per row of the whole puzzle. Cells
+/// outside every grid are written as `outside`, and may be left out at the end of a line.
+#[derive(Debug)]
+pub struct CompositeAscii {
+    empty_cell: u8,
+    outside: u8,
+}
+
+impl Default for CompositeAscii {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+impl CompositeAscii {
+    pub fn new(empty_cell: Option<char>, outside: Option<char>) -> Self {
+        let empty_cell: u8 = empty_cell.unwrap_or('_').try_into().unwrap();
+        let outside: u8 = outside.unwrap_or(' ').try_into().unwrap();
+        assert!(empty_cell != outside && !empty_cell.is_ascii_digit() && !outside.is_ascii_digit());
+        Self {
+            empty_cell,
+            outside,
+        }
+    }
+
+    pub fn read<R>(
+        &self,
+        layout: CompositeLayout,
+        reader: &mut R,
+    ) -> Result<CompositeGrid, CompositeAsciiReadError>
+    where
+        R: Read,
+    {
+        let mut s = String::new();
+        reader
+            .read_to_string(&mut s)
+            .map_err(|_| CompositeAsciiReadError::Io)?;
+        let mut grid = CompositeGrid::new(layout);
+        let mut lines = s.lines();
+        for row in 0..grid.layout().height() {
+            let syntax = || CompositeAsciiReadError::Syntax { line: row + 1 };
+            let text = lines.next().ok_or_else(syntax)?.as_bytes();
+            if text.len() > grid.layout().width() {
+                return Err(syntax());
+            }
+            for col in 0..grid.layout().width() {
+                let idx = SizedCellIdx { row, col };
+                let c = text.get(col).copied().unwrap_or(self.outside);
+                match (grid.layout().contains(idx), c) {
+                    (false, c) if c == self.outside => (),
+                    (true, c) if c == self.empty_cell => (),
+                    (true, b'1'..=b'9') => grid[idx] = Some(Digit::try_from(c - b'1').unwrap()),
+                    _ => return Err(syntax()),
+                }
+            }
+        }
+        if lines.any(|text| !text.trim().is_empty()) {
+            return Err(CompositeAsciiReadError::Syntax {
+                line: grid.layout().height() + 1,
+            });
+        }
+        Ok(grid)
+    }
+
+    pub fn read_from_string(
+        &self,
+        layout: CompositeLayout,
+        s: &str,
+    ) -> Result<CompositeGrid, CompositeAsciiReadError> {
+        self.read(layout, &mut Cursor::new(s.as_bytes()))
+    }
+
+    pub fn write<W>(&self, grid: &CompositeGrid, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: Write,
+    {
+        let layout = grid.layout();
+        (0..layout.height()).try_fold(0, |res, row| {
+            let mut line = (0..layout.width())
+                .map(|col| {
+                    let idx = SizedCellIdx { row, col };
+                    match grid[idx] {
+                        Some(value) => b'1' + u8::from(value),
+                        None if layout.contains(idx) => self.empty_cell,
+                        None => self.outside,
+                    }
+                })
+                .collect::<Vec<_>>();
+            while line.last() == Some(&self.outside) {
+                line.pop();
+            }
+            if row + 1 != layout.height() {
+                line.push(b'\n');
+            }
+            writer.write_all(&line)?;
+            Ok(res + line.len())
+        })
+    }
+
+    pub fn write_string(&self, grid: &CompositeGrid) -> String {
+        let mut cursor = Cursor::new(Vec::new());
+        self.write(grid, &mut cursor).unwrap();
+        String::from_utf8(cursor.into_inner()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod composite_ascii_test {
+    use super::{
+        CompositeAscii, CompositeAsciiReadError, CompositeLayoutAscii,
+        CompositeLayoutAsciiReadError,
+    };
+    use crate::grid::{CompositeLayout, CompositeLayoutError, Digit, SizedCellIdx};
+
+    #[test]
+    fn test_layout() {
+        let f = CompositeLayoutAscii::new();
+        let samurai = CompositeLayout::samurai();
+        assert_eq!(samurai, f.read_from_string("# Weekly\nSamurai\n").unwrap());
+        assert_eq!(
+            samurai,
+            f.read_from_string(&f.write_string(&samurai)).unwrap()
+        );
+        assert_eq!("1 1\n7 7\n", f.write_string(&CompositeLayout::twodoku()));
+        assert_eq!(
+            Err(CompositeLayoutAsciiReadError::Syntax { line: 1 }),
+            f.read_from_string("samurai2")
+        );
+        assert_eq!(
+            Err(CompositeLayoutAsciiReadError::Syntax { line: 2 }),
+            f.read_from_string("1 1\n0 7")
+        );
+        assert_eq!(
+            Err(CompositeLayoutAsciiReadError::Layout(
+                CompositeLayoutError::NotAligned
+            )),
+            f.read_from_string("1 1\n5 5")
+        );
+    }
+
+    #[test]
+    fn test_grid() {
+        let expected = r#"
+1________
+_________
+_________
+_________
+_________
+_________
+______________2
+_______________
+_______________
+      _________
+      _________
+      _________
+      _________
+      _________
+      ________9"#
+            .trim_start_matches('\n');
+        let f = CompositeAscii::default();
+        let grid = f
+            .read_from_string(CompositeLayout::twodoku(), expected)
+            .unwrap();
+        assert_eq!(
+            vec![
+                (SizedCellIdx { row: 0, col: 0 }, Digit::D1),
+                (SizedCellIdx { row: 6, col: 14 }, Digit::D2),
+                (SizedCellIdx { row: 14, col: 14 }, Digit::D9),
+            ],
+            grid.iter_set().collect::<Vec<_>>()
+        );
+        assert_eq!(expected, f.write_string(&grid));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let f = CompositeAscii::default();
+        let layout = CompositeLayout::twodoku;
+        let rows = |first: &str| {
+            [first]
+                .into_iter()
+                .chain(["_________"; 5])
+                .chain(["_______________"; 3])
+                .chain(["      _________"; 6])
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        assert!(f.read_from_string(layout(), &rows("_________")).is_ok());
+        // Outside the first grid.
+        assert_eq!(
+            Err(CompositeAsciiReadError::Syntax { line: 1 }),
+            f.read_from_string(layout(), &rows("__________"))
+        );
+        assert_eq!(
+            Err(CompositeAsciiReadError::Syntax { line: 1 }),
+            f.read_from_string(layout(), &rows("____0____"))
+        );
+        assert_eq!(
+            Err(CompositeAsciiReadError::Syntax { line: 1 }),
+            f.read_from_string(layout(), &rows("___ _____"))
+        );
+        assert_eq!(
+            Err(CompositeAsciiReadError::Syntax { line: 16 }),
+            f.read_from_string(layout(), &(rows("_________") + "\n1"))
+        );
+        assert_eq!(
+            Err(CompositeAsciiReadError::Syntax { line: 3 }),
+            f.read_from_string(layout(), "_________\n_________")
+        );
+    }
+}
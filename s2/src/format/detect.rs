@@ -0,0 +1,217 @@
+use crate::grid::{CellIdx, Digit, GridMutWithDefault, DIM};
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+#[error("input matches none of the known notations")]
+pub struct UnknownFormatError;
+
+/// Plain-text notations `detect_and_read` recognizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectedFormat {
+    /// 81 cells on a single line, `.` or `0` for empty cells.
+    SingleLine,
+    /// 9 lines of 9 cells, as `RowMajorAscii` writes with its defaults. Empty cells are `.`, `0`
+    /// or `_`.
+    RowMajor,
+    /// Rows with `|` between boxes and lines of `-` and `+` between bands. Empty cells are `.`,
+    /// `0` or `_`.
+    Bordered,
+    /// 81 cells separated by whitespace, `.` or `0` for empty cells.
+    Whitespace,
+}
+
+/// Digit of a cell written as `c`, `Some(None)` for an empty cell in `blanks`.
+fn cell(c: char, blanks: &str) -> Option<Option<Digit>> {
+    match c {
+        '1'..='9' => Some(Digit::try_from_ascii(c as u8).ok()),
+        c if blanks.contains(c) => Some(None),
+        _ => None,
+    }
+}
+
+fn grid_of<G, I>(cells: I) -> Option<G>
+where
+    G: GridMutWithDefault,
+    I: Iterator<Item = Option<Option<Digit>>>,
+{
+    let cells = cells.collect::<Option<Vec<_>>>()?;
+    if cells.len() != CellIdx::COUNT {
+        return None;
+    }
+    let mut grid = G::default();
+    for (idx, value) in CellIdx::iter_row_wise().zip(cells) {
+        grid[idx] = value;
+    }
+    Some(grid)
+}
+
+fn single_line<G: GridMutWithDefault>(lines: &[&str]) -> Option<G> {
+    match lines {
+        [line] => grid_of(line.chars().map(|c| cell(c, ".0"))),
+        _ => None,
+    }
+}
+
+fn bordered<G: GridMutWithDefault>(lines: &[&str]) -> Option<G> {
+    if !lines.iter().any(|line| line.contains(['|', '+'])) {
+        return None;
+    }
+    let rows = lines
+        .iter()
+        .filter(|line| !line.chars().all(|c| "-+|= ".contains(c)))
+        .collect::<Vec<_>>();
+    if rows.len() != DIM {
+        return None;
+    }
+    let cells = rows
+        .iter()
+        .map(|row| {
+            row.chars()
+                .filter(|c| *c != '|' && !c.is_whitespace())
+                .map(|c| cell(c, ".0_"))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    if cells.iter().any(|row| row.len() != DIM) {
+        return None;
+    }
+    grid_of(cells.into_iter().flatten())
+}
+
+fn row_major<G: GridMutWithDefault>(lines: &[&str]) -> Option<G> {
+    if lines.len() != DIM || lines.iter().any(|line| line.chars().count() != DIM) {
+        return None;
+    }
+    grid_of(
+        lines
+            .iter()
+            .flat_map(|line| line.chars())
+            .map(|c| cell(c, ".0_")),
+    )
+}
+
+fn whitespace<G: GridMutWithDefault>(input: &str) -> Option<G> {
+    grid_of(input.split_whitespace().map(|token| {
+        let mut chars = token.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => cell(c, ".0"),
+            _ => None,
+        }
+    }))
+}
+
+/// Reads a grid in whichever of the `DetectedFormat` notations `input` is written in. Blank lines
+/// and blanks around lines are ignored.
+pub fn detect_and_read<G>(input: &str) -> Result<(G, DetectedFormat), UnknownFormatError>
+where
+    G: GridMutWithDefault,
+{
+    let lines = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    if let Some(grid) = single_line(&lines) {
+        return Ok((grid, DetectedFormat::SingleLine));
+    }
+    if let Some(grid) = bordered(&lines) {
+        return Ok((grid, DetectedFormat::Bordered));
+    }
+    if let Some(grid) = row_major(&lines) {
+        return Ok((grid, DetectedFormat::RowMajor));
+    }
+    if let Some(grid) = whitespace(input) {
+        return Ok((grid, DetectedFormat::Whitespace));
+    }
+    Err(UnknownFormatError)
+}
+
+#[cfg(test)]
+mod detect_test {
+    use super::{detect_and_read, DetectedFormat, UnknownFormatError};
+    use crate::format::{read_from_string, RowMajorAscii};
+    use crate::grid::ArrGridRowMajor;
+
+    const EXPECTED: &str = r#"
+53__7____
+6__195___
+_98____6_
+8___6___3
+4__8_3__1
+7___2___6
+_6____28_
+___419__5
+____8__79
+"#;
+
+    fn read(s: &str) -> Result<(ArrGridRowMajor, DetectedFormat), UnknownFormatError> {
+        detect_and_read(s)
+    }
+
+    #[test]
+    fn test_formats() {
+        let expected: ArrGridRowMajor =
+            read_from_string(&RowMajorAscii::default(), EXPECTED.trim()).unwrap();
+        let single_line = EXPECTED.trim().replace('\n', "");
+        for blank in ["_", ".", "0"] {
+            assert_eq!(
+                Ok((expected, DetectedFormat::RowMajor)),
+                read(&EXPECTED.replace('_', blank))
+            );
+        }
+        assert_eq!(
+            Ok((expected, DetectedFormat::SingleLine)),
+            read(&single_line.replace('_', "."))
+        );
+        assert_eq!(
+            Ok((expected, DetectedFormat::SingleLine)),
+            read(&format!("  {}\n", single_line.replace('_', "0")))
+        );
+        let bordered = r#"
++-------+-------+-------+
+| 5 3 . | . 7 . | . . . |
+| 6 . . | 1 9 5 | . . . |
+| . 9 8 | . . . | . 6 . |
++-------+-------+-------+
+| 8 . . | . 6 . | . . 3 |
+| 4 . . | 8 . 3 | . . 1 |
+| 7 . . | . 2 . | . . 6 |
++-------+-------+-------+
+| . 6 . | . . . | 2 8 . |
+| . . . | 4 1 9 | . . 5 |
+| . . . | . 8 . | . 7 9 |
++-------+-------+-------+
+"#;
+        assert_eq!(Ok((expected, DetectedFormat::Bordered)), read(bordered));
+        let compact = "53_|_7_|___\n6__|195|___\n_98|___|_6_\n---+---+---\n8__|_6_|__3\n4__|8_3|__1\n7__|_2_|__6\n---+---+---\n_6_|___|28_\n___|419|__5\n___|_8_|_79";
+        assert_eq!(Ok((expected, DetectedFormat::Bordered)), read(compact));
+        let spaced = EXPECTED
+            .trim()
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| if c == '_' { "0".into() } else { c.to_string() })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(Ok((expected, DetectedFormat::Whitespace)), read(&spaced));
+        assert_eq!(
+            Ok((expected, DetectedFormat::Whitespace)),
+            read(&spaced.replace('\n', " ").replace('0', "."))
+        );
+    }
+
+    #[test]
+    fn test_unknown() {
+        assert_eq!(Err(UnknownFormatError), read(""));
+        assert_eq!(Err(UnknownFormatError), read(&"1".repeat(80)));
+        assert_eq!(Err(UnknownFormatError), read(&"x".repeat(81)));
+        assert_eq!(
+            Err(UnknownFormatError),
+            read(&EXPECTED.replacen('_', "x", 1))
+        );
+        assert_eq!(Err(UnknownFormatError), read(&"12 ".repeat(81)));
+    }
+}
@@ -0,0 +1,421 @@
+use super::{ReadFormatter, WriteFormatter};
+use crate::grid::{
+    ArrGridRowMajor, CellIdx, ColIdx, Digit, Grid, GridMut, Pair, Relation, RowIdx, DIM,
+};
+use std::io::{Read, Write};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum GreaterThanAsciiReadError {
+    #[error("Reading a grid fails")]
+    Io,
+    #[error("line {line} is neither a row of cells nor a line of `^` and `v` signs")]
+    Syntax { line: usize },
+    #[error("signs differ from the pairs of the formatter")]
+    Signs,
+}
+
+/// `RowMajorAscii` with greater-than signs: `<` or `>` between cells of a row, and lines of `^`
+/// or `v` between rows, each sign under the cell above it. Signs point at the smaller digit, so
+/// `^` means the upper digit is smaller. Blanks between cells are optional, so a grid without
+/// signs in `RowMajorAscii` reads as is.
+///
+/// Signs are written from the pairs the formatter carries. Reading as a `ReadFormatter` takes the
+/// digits and skips the signs, unless `strict` makes it require the signs of these pairs.
+/// `read_pairs` returns whichever signs there are.
+///
+/// ```text
+/// 5<_ _ _
+/// ^     v
+/// _>3 _ _
+/// ```
+#[derive(Debug)]
+pub struct GreaterThanAscii {
+    empty_cell: u8,
+    pairs: Vec<Pair>,
+    strict: bool,
+}
+
+impl Default for GreaterThanAscii {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+/// Whether the first cell of a greater-than sign between `a` and `b` is `a`, if there is a sign.
+fn sign(pairs: &[Pair], a: CellIdx, b: CellIdx) -> Option<bool> {
+    pairs
+        .iter()
+        .filter(|pair| pair.relation() == Relation::Less)
+        .find_map(|pair| match pair.cells() {
+            [x, y] if (*x, *y) == (a, b) => Some(true),
+            [x, y] if (*x, *y) == (b, a) => Some(false),
+            _ => None,
+        })
+}
+
+impl GreaterThanAscii {
+    pub fn new(empty_cell: Option<char>) -> Self {
+        let empty_cell: u8 = empty_cell.unwrap_or('_').try_into().unwrap();
+        assert!(!b"<>^v 123456789".contains(&empty_cell));
+        Self {
+            empty_cell,
+            pairs: Vec::new(),
+            strict: false,
+        }
+    }
+
+    /// Writes the signs of `pairs`, leaving out relations other than `Relation::Less`.
+    pub fn with_pairs(mut self, pairs: Vec<Pair>) -> Self {
+        self.pairs = pairs;
+        self
+    }
+
+    /// Fails reading as a `ReadFormatter` with `GreaterThanAsciiReadError::Signs` if the signs
+    /// differ from those of the pairs of the formatter.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    pub fn pairs(&self) -> &[Pair] {
+        &self.pairs
+    }
+
+    /// Reads the digits into `grid` and returns the signs as pairs with `Relation::Less`. `grid`
+    /// is left as is on error.
+    pub fn read_pairs<R, G>(
+        &self,
+        reader: &mut R,
+        grid: &mut G,
+    ) -> Result<Vec<Pair>, GreaterThanAsciiReadError>
+    where
+        R: Read,
+        G: GridMut + ?Sized,
+    {
+        let mut s = String::new();
+        reader
+            .read_to_string(&mut s)
+            .map_err(|_| GreaterThanAsciiReadError::Io)?;
+        let at = |row: usize, col: usize| -> CellIdx {
+            (
+                RowIdx::try_from(row).unwrap(),
+                ColIdx::try_from(col).unwrap(),
+            )
+                .into()
+        };
+        let less = |smaller, larger| Pair::new(smaller, larger, Relation::Less).unwrap();
+        let mut pairs = Vec::new();
+        let mut digits = ArrGridRowMajor::new();
+        let mut row = 0;
+        // Offset of each cell of the row above, and the signs below it once read.
+        let mut above = Vec::new();
+        let mut below: Option<Vec<(usize, u8)>> = None;
+        let mut last = 0;
+        for (i, text) in s.lines().enumerate() {
+            let line = i + 1;
+            let syntax = || GreaterThanAsciiReadError::Syntax { line };
+            let text = text.trim_end().as_bytes();
+            last = line;
+            if text.is_empty() {
+                continue;
+            }
+            if text.iter().all(|c| b"^v ".contains(c)) {
+                if row == 0 || row == DIM || below.is_some() {
+                    return Err(syntax());
+                }
+                let signs = text
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| **c != b' ')
+                    .map(|(x, c)| Some((above.iter().position(|offset| *offset == x)?, *c)))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(syntax)?;
+                below = Some(signs);
+                continue;
+            }
+            if row == DIM {
+                return Err(syntax());
+            }
+            let mut cells = Vec::with_capacity(DIM);
+            let mut pending = None;
+            for (x, c) in text.iter().enumerate() {
+                let value = match *c {
+                    c if c.is_ascii_whitespace() => continue,
+                    b'<' | b'>' if !cells.is_empty() && pending.is_none() => {
+                        pending = Some(*c);
+                        continue;
+                    }
+                    b'1'..=b'9' => Some(Digit::try_from_ascii(*c).unwrap()),
+                    c if c == self.empty_cell => None,
+                    _ => return Err(syntax()),
+                };
+                if cells.len() == DIM {
+                    return Err(syntax());
+                }
+                let col = cells.len();
+                match pending.take() {
+                    Some(b'<') => pairs.push(less(at(row, col - 1), at(row, col))),
+                    Some(_) => pairs.push(less(at(row, col), at(row, col - 1))),
+                    None => (),
+                }
+                cells.push((x, value));
+            }
+            if cells.len() != DIM || pending.is_some() {
+                return Err(syntax());
+            }
+            for (col, c) in below.take().into_iter().flatten() {
+                match c {
+                    b'^' => pairs.push(less(at(row - 1, col), at(row, col))),
+                    _ => pairs.push(less(at(row, col), at(row - 1, col))),
+                }
+            }
+            for (col, (_, value)) in cells.iter().enumerate() {
+                digits[at(row, col)] = *value;
+            }
+            above = cells.into_iter().map(|(x, _)| x).collect();
+            row += 1;
+        }
+        if row != DIM || below.is_some() {
+            return Err(GreaterThanAsciiReadError::Syntax { line: last + 1 });
+        }
+        CellIdx::iter_row_wise().for_each(|idx| grid[idx] = digits[idx]);
+        Ok(pairs)
+    }
+
+    pub fn read_pairs_from_string<G>(
+        &self,
+        s: &str,
+        grid: &mut G,
+    ) -> Result<Vec<Pair>, GreaterThanAsciiReadError>
+    where
+        G: GridMut + ?Sized,
+    {
+        self.read_pairs(&mut s.as_bytes(), grid)
+    }
+}
+
+/// Cells of the greater-than signs among `pairs`, smaller first, in a set order.
+fn signs_of(pairs: &[Pair]) -> Vec<[usize; 2]> {
+    let mut signs = pairs
+        .iter()
+        .filter(|pair| pair.relation() == Relation::Less)
+        .map(|pair| [pair.cells()[0].row_major(), pair.cells()[1].row_major()])
+        .collect::<Vec<_>>();
+    signs.sort_unstable();
+    signs
+}
+
+impl ReadFormatter for GreaterThanAscii {
+    type ReadError = GreaterThanAsciiReadError;
+
+    fn read<R, G>(&self, reader: &mut R, grid: &mut G) -> Result<(), Self::ReadError>
+    where
+        R: Read,
+        G: GridMut + ?Sized,
+    {
+        let mut digits = ArrGridRowMajor::new();
+        let pairs = self.read_pairs(reader, &mut digits)?;
+        if self.strict && signs_of(&pairs) != signs_of(&self.pairs) {
+            return Err(GreaterThanAsciiReadError::Signs);
+        }
+        CellIdx::iter_row_wise().for_each(|idx| grid[idx] = digits[idx]);
+        Ok(())
+    }
+}
+
+impl WriteFormatter for GreaterThanAscii {
+    fn write<G, W>(&self, grid: &G, writer: &mut W) -> std::io::Result<usize>
+    where
+        G: Grid + ?Sized,
+        W: Write,
+    {
+        RowIdx::iter().enumerate().try_fold(0, |res, (i, row)| {
+            let mut line = Vec::new();
+            for (j, col) in ColIdx::iter().enumerate() {
+                let idx: CellIdx = (row, col).into();
+                if j > 0 {
+                    let left = ColIdx::try_from(j - 1).unwrap();
+                    line.push(match sign(&self.pairs, (row, left).into(), idx) {
+                        Some(true) => b'<',
+                        Some(false) => b'>',
+                        None => b' ',
+                    });
+                }
+                line.push(grid[idx].map_or(self.empty_cell, |x| x.as_ascii()));
+            }
+            if let Ok(next) = RowIdx::try_from(i + 1) {
+                line.push(b'\n');
+                let mut signs = ColIdx::iter()
+                    .flat_map(|col| {
+                        let c = match sign(&self.pairs, (row, col).into(), (next, col).into()) {
+                            Some(true) => b'^',
+                            Some(false) => b'v',
+                            None => b' ',
+                        };
+                        [c, b' ']
+                    })
+                    .collect::<Vec<_>>();
+                while signs.last() == Some(&b' ') {
+                    signs.pop();
+                }
+                if !signs.is_empty() {
+                    line.extend(signs);
+                    line.push(b'\n');
+                }
+            }
+            writer.write_all(&line)?;
+            Ok(res + line.len())
+        })
+    }
+}
+
+#[cfg(test)]
+mod greater_than_ascii_test {
+    use super::{GreaterThanAscii, GreaterThanAsciiReadError};
+    use crate::format::{read_from_string, write_string, ReadFormatter, RowMajorAscii};
+    use crate::grid::{ArrGridRowMajor, CellIdx, Digit, Pair};
+
+    fn idx(x: usize) -> CellIdx {
+        CellIdx::try_of_row_major(x).unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let expected = r#"
+5<_ _ _ _ _ _ _ _
+^               v
+_>3 _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+  v
+_ _ _ _ _ _ _ _<9"#
+            .trim_start_matches('\n');
+        let f = GreaterThanAscii::default();
+        let mut grid = ArrGridRowMajor::new();
+        let pairs = f.read_pairs_from_string(expected, &mut grid).unwrap();
+        assert_eq!(
+            Pair::greater_than(&[
+                (idx(0), idx(1)),
+                (idx(10), idx(9)),
+                (idx(0), idx(9)),
+                (idx(17), idx(8)),
+                (idx(79), idx(80)),
+                (idx(73), idx(64)),
+            ])
+            .unwrap(),
+            pairs
+        );
+        assert_eq!(Some(Digit::D3), grid[idx(10)]);
+        let f = f.with_pairs(pairs);
+        assert_eq!(expected, write_string(&f, &grid));
+        assert_eq!(Ok(grid), read_from_string(&f, expected));
+    }
+
+    #[test]
+    fn test_signs() {
+        let s = "5<_ _ _ _ _ _ _ _\n".to_string() + &["_ _ _ _ _ _ _ _ _"; 8].join("\n");
+        let pairs = Pair::greater_than(&[(idx(0), idx(1))]).unwrap();
+        let f = GreaterThanAscii::default().with_pairs(pairs.clone());
+        assert!(read_from_string::<_, ArrGridRowMajor>(&f, &s).is_ok());
+        assert!(read_from_string::<_, ArrGridRowMajor>(&f.strict(), &s).is_ok());
+        // Only the digits are read unless strict.
+        let mut grid = ArrGridRowMajor::new();
+        assert_eq!(
+            Ok(()),
+            GreaterThanAscii::default().read_from_string(&s, &mut grid)
+        );
+        assert_eq!(Some(Digit::D5), grid[idx(0)]);
+        // Signs are checked before the grid is touched.
+        let mut grid = ArrGridRowMajor::new();
+        assert_eq!(
+            Err(GreaterThanAsciiReadError::Signs),
+            GreaterThanAscii::default()
+                .strict()
+                .read_from_string(&s, &mut grid)
+        );
+        assert_eq!(ArrGridRowMajor::new(), grid);
+        let f = GreaterThanAscii::default().strict().with_pairs(
+            Pair::greater_than(&[(idx(1), idx(0))])
+                .unwrap()
+                .into_iter()
+                .chain(pairs)
+                .collect(),
+        );
+        assert_eq!(
+            Err(GreaterThanAsciiReadError::Signs),
+            f.read_from_string(&s, &mut grid)
+        );
+    }
+
+    #[test]
+    fn test_row_major() {
+        let s = "53__7____\n6__195___\n_98____6_\n8___6___3\n4__8_3__1\n7___2___6\n_6____28_\n___419__5\n____8__79";
+        let expected: ArrGridRowMajor = read_from_string(&RowMajorAscii::default(), s).unwrap();
+        let mut grid = ArrGridRowMajor::new();
+        let pairs = GreaterThanAscii::default()
+            .read_pairs_from_string(s, &mut grid)
+            .unwrap();
+        assert!(pairs.is_empty());
+        assert_eq!(expected, grid);
+        // Compact rows take signs as well, under the offsets of their cells.
+        let s = s.replacen("53", "5>3", 1).replacen("\n", "\n  v\n", 1);
+        let pairs = GreaterThanAscii::default()
+            .read_pairs_from_string(&s, &mut grid)
+            .unwrap();
+        assert_eq!(
+            Pair::greater_than(&[(idx(1), idx(0)), (idx(10), idx(1))]).unwrap(),
+            pairs
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let f = GreaterThanAscii::default();
+        let mut grid = ArrGridRowMajor::new();
+        let rows = ["_________"; 9].join("\n");
+        let read = |s: &str| f.read_pairs_from_string(s, &mut ArrGridRowMajor::new());
+        assert!(f.read_pairs_from_string(&rows, &mut grid).is_ok());
+        assert_eq!(
+            Err(GreaterThanAsciiReadError::Syntax { line: 1 }),
+            read(&rows.replacen("__", "<_", 1))
+        );
+        assert_eq!(
+            Err(GreaterThanAsciiReadError::Syntax { line: 1 }),
+            read(&rows.replacen("__", "_<<_", 1))
+        );
+        assert_eq!(
+            Err(GreaterThanAsciiReadError::Syntax { line: 1 }),
+            read(&rows.replacen("\n", "<\n", 1))
+        );
+        // Signs must sit under a cell, and between two rows.
+        assert_eq!(
+            Err(GreaterThanAsciiReadError::Syntax { line: 2 }),
+            read(&rows.replacen("\n", "\n          ^\n", 1))
+        );
+        assert_eq!(
+            Err(GreaterThanAsciiReadError::Syntax { line: 1 }),
+            read(&("^\n".to_string() + &rows))
+        );
+        assert_eq!(
+            Err(GreaterThanAsciiReadError::Syntax { line: 10 }),
+            read(&(rows.clone() + "\nv"))
+        );
+        assert_eq!(
+            Err(GreaterThanAsciiReadError::Syntax { line: 9 }),
+            read(&rows[..80])
+        );
+        // Digits read before the error stay out of the grid.
+        let mut grid = ArrGridRowMajor::new();
+        assert_eq!(
+            Err(GreaterThanAsciiReadError::Syntax { line: 9 }),
+            f.read_pairs_from_string(&rows.replacen('_', "5", 1)[..80], &mut grid)
+        );
+        assert_eq!(ArrGridRowMajor::new(), grid);
+    }
+}
@@ -3,7 +3,38 @@ use std::io::{Cursor, Read, Write};
 use strum::EnumCount;
 
 mod row_major_ascii;
-pub use row_major_ascii::RowMajorAscii;
+pub use row_major_ascii::{
+    Expected, RowMajorAscii, RowMajorAsciiReadError, RowMajorAsciiSymbolsError, TextPosition,
+};
+
+mod box_layout_ascii;
+pub use box_layout_ascii::{BoxLayoutAscii, BoxLayoutAsciiReadError};
+
+mod outside_clues_ascii;
+pub use outside_clues_ascii::{OutsideCluesAscii, OutsideCluesReadError};
+
+mod greater_than_ascii;
+pub use greater_than_ascii::{GreaterThanAscii, GreaterThanAsciiReadError};
+
+mod box_drawing;
+pub use box_drawing::{Borders, BoxDrawing};
+
+mod sadman;
+pub use sadman::{PuzzleFile, PuzzleFileReadError, PuzzleInfo, SadManSdk, SadManSdx};
+
+mod simple_sudoku;
+pub use simple_sudoku::SimpleSudokuSs;
+
+mod packed;
+pub use packed::{read_records, write_records, PackedPuzzle, PackedReadError, PackedSolution};
+
+mod detect;
+pub use detect::{detect_and_read, DetectedFormat, UnknownFormatError};
+
+mod composite_ascii;
+pub use composite_ascii::{
+    CompositeAscii, CompositeAsciiReadError, CompositeLayoutAscii, CompositeLayoutAsciiReadError,
+};
 
 pub trait ReadFormatter {
     type ReadError;
@@ -0,0 +1,174 @@
+use crate::grid::{ClueKind, OutsideClue, OutsideClueError, Side};
+use std::io::{Cursor, Read, Write};
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum OutsideCluesReadError {
+    #[error("Reading clues fails")]
+    Io,
+    #[error("line {line} is not `<kind> <side> <index> <value>`")]
+    Syntax { line: usize },
+    #[error("invalid clue on line {line}")]
+    Clue {
+        line: usize,
+        #[source]
+        source: OutsideClueError,
+    },
+}
+
+/// Text format for outside clues, one per line as `<kind> <side> <index> <value>`, such as
+/// `sandwich left 3 15`. Kinds are `sandwich`, `skyscraper` and `xsum`, sides are `top`,
+/// `bottom`, `left` and `right`, and rows and columns are numbered from 1. Blank lines and lines
+/// starting with `#` are skipped.
+#[derive(Debug, Default)]
+pub struct OutsideCluesAscii;
+
+fn kind_name(kind: ClueKind) -> &'static str {
+    match kind {
+        ClueKind::Sandwich => "sandwich",
+        ClueKind::Skyscraper => "skyscraper",
+        ClueKind::XSum => "xsum",
+    }
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Top => "top",
+        Side::Bottom => "bottom",
+        Side::Left => "left",
+        Side::Right => "right",
+    }
+}
+
+impl OutsideCluesAscii {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn read<R>(&self, reader: &mut R) -> Result<Vec<OutsideClue>, OutsideCluesReadError>
+    where
+        R: Read,
+    {
+        let mut s = String::new();
+        reader
+            .read_to_string(&mut s)
+            .map_err(|_| OutsideCluesReadError::Io)?;
+        s.lines()
+            .enumerate()
+            .map(|(i, text)| (i + 1, text.trim()))
+            .filter(|(_, text)| !text.is_empty() && !text.starts_with('#'))
+            .map(|(line, text)| {
+                let syntax = || OutsideCluesReadError::Syntax { line };
+                let [kind, side, index, value] = text
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .map_err(|_| syntax())?;
+                let kind = [ClueKind::Sandwich, ClueKind::Skyscraper, ClueKind::XSum]
+                    .into_iter()
+                    .find(|x| kind_name(*x).eq_ignore_ascii_case(kind))
+                    .ok_or_else(syntax)?;
+                let side = [Side::Top, Side::Bottom, Side::Left, Side::Right]
+                    .into_iter()
+                    .find(|x| side_name(*x).eq_ignore_ascii_case(side))
+                    .ok_or_else(syntax)?;
+                let index = index
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|x| x.checked_sub(1))
+                    .ok_or_else(syntax)?;
+                let value = value.parse::<u8>().map_err(|_| syntax())?;
+                OutsideClue::new(kind, side, index, value)
+                    .map_err(|source| OutsideCluesReadError::Clue { line, source })
+            })
+            .collect()
+    }
+
+    pub fn read_from_string(&self, s: &str) -> Result<Vec<OutsideClue>, OutsideCluesReadError> {
+        self.read(&mut Cursor::new(s.as_bytes()))
+    }
+
+    pub fn write<W>(&self, clues: &[OutsideClue], writer: &mut W) -> std::io::Result<usize>
+    where
+        W: Write,
+    {
+        clues.iter().try_fold(0, |res, clue| {
+            let line = format!(
+                "{} {} {} {}\n",
+                kind_name(clue.kind()),
+                side_name(clue.side()),
+                clue.index() + 1,
+                clue.value()
+            );
+            writer.write_all(line.as_bytes())?;
+            Ok(res + line.len())
+        })
+    }
+
+    pub fn write_string(&self, clues: &[OutsideClue]) -> String {
+        let mut cursor = Cursor::new(Vec::new());
+        self.write(clues, &mut cursor).unwrap();
+        String::from_utf8(cursor.into_inner()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod outside_clues_ascii_test {
+    use super::{OutsideCluesAscii, OutsideCluesReadError};
+    use crate::grid::{ClueKind, OutsideClue, OutsideClueError, Side};
+
+    #[test]
+    fn test_roundtrip() {
+        let expected = r#"
+sandwich left 1 15
+skyscraper top 4 3
+xsum right 9 21
+"#
+        .trim_start();
+        let f = OutsideCluesAscii::new();
+        let clues = f.read_from_string(expected).unwrap();
+        assert_eq!(
+            OutsideClue::new(ClueKind::Skyscraper, Side::Top, 3, 3).unwrap(),
+            clues[1]
+        );
+        assert_eq!(expected, f.write_string(&clues));
+        assert_eq!(
+            clues,
+            f.read_from_string(
+                "# Comment\n\n  SANDWICH Left 1 15\nskyscraper top 4 3\nxsum right 9 21"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let f = OutsideCluesAscii::new();
+        assert_eq!(
+            Err(OutsideCluesReadError::Syntax { line: 2 }),
+            f.read_from_string("xsum top 1 1\nxsum top 1")
+        );
+        assert_eq!(
+            Err(OutsideCluesReadError::Syntax { line: 1 }),
+            f.read_from_string("xsum top 0 1")
+        );
+        assert_eq!(
+            Err(OutsideCluesReadError::Syntax { line: 1 }),
+            f.read_from_string("killer top 1 1")
+        );
+        assert_eq!(
+            Err(OutsideCluesReadError::Clue {
+                line: 1,
+                source: OutsideClueError::Index
+            }),
+            f.read_from_string("xsum top 10 1")
+        );
+        assert_eq!(
+            Err(OutsideCluesReadError::Clue {
+                line: 1,
+                source: OutsideClueError::Infeasible
+            }),
+            f.read_from_string("skyscraper top 1 0")
+        );
+    }
+}
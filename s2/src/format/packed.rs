@@ -0,0 +1,469 @@
+use super::{ReadFormatter, WriteFormatter};
+use crate::grid::{CellIdx, ColIdx, Digit, Grid, GridMut, GridMutWithDefault, RowIdx, DIM};
+use crate::status::{eval_status, SudokuStatus};
+use std::io::{ErrorKind, Read, Write};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum PackedReadError {
+    #[error("Reading a record fails")]
+    Io,
+    #[error("input ends before the record starts")]
+    EndOfInput,
+    #[error("input ends in the middle of a record")]
+    Truncated,
+    #[error("record does not encode a grid")]
+    Invalid,
+}
+
+/// Bits of the 81-cell given mask.
+const MASK_BITS: usize = CellIdx::COUNT;
+/// Bytes every puzzle record starts with, enough to hold the mask.
+const MASK_LEN: usize = MASK_BITS.div_ceil(8);
+/// Bits of a row rank, as 9! < 2^19.
+const RANK_BITS: usize = 19;
+const FACTORIALS: [u32; DIM] = [1, 1, 2, 6, 24, 120, 720, 5040, 40320];
+
+/// Bit string written from the most significant bit of each byte on.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    fn push(&mut self, value: u32, bits: usize) {
+        for k in (0..bits).rev() {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if value & (1 << k) != 0 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn take(&mut self, bits: usize) -> u32 {
+        (0..bits).fold(0, |acc, _| {
+            let bit = self.bytes[self.pos / 8] >> (7 - self.pos % 8) & 1;
+            self.pos += 1;
+            acc << 1 | u32::from(bit)
+        })
+    }
+
+    /// Whether the bits left over in the last byte are all zero.
+    fn padding_is_zero(&mut self) -> bool {
+        let left = self.bytes.len() * 8 - self.pos;
+        self.take(left) == 0
+    }
+}
+
+/// Fills `buf` from `reader`, telling a reader that is already exhausted from one that stops
+/// part way.
+fn read_record<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<(), PackedReadError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Err(PackedReadError::EndOfInput),
+            Ok(0) => return Err(PackedReadError::Truncated),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => (),
+            Err(_) => return Err(PackedReadError::Io),
+        }
+    }
+    Ok(())
+}
+
+/// Puzzles in a few dozen bytes: an 81-bit mask of the cells with givens, row by row, followed by
+/// 4 bits per given digit, padded with zeros to a whole byte. A record takes
+/// `PackedPuzzle::len(givens)` bytes, so 23 bytes for a puzzle with 25 givens and 11 for an empty
+/// grid.
+#[derive(Debug, Default)]
+pub struct PackedPuzzle;
+
+impl PackedPuzzle {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Bytes of a record of a puzzle with `givens` digits.
+    pub fn len(givens: usize) -> usize {
+        (MASK_BITS + 4 * givens).div_ceil(8)
+    }
+
+    pub fn encode<G>(&self, grid: &G) -> Vec<u8>
+    where
+        G: Grid + ?Sized,
+    {
+        let mut bits = BitWriter::default();
+        for (_, value) in grid.iter_row_wise() {
+            bits.push(value.is_some().into(), 1);
+        }
+        for (_, value) in grid.iter_set_row_wise() {
+            bits.push(u8::from(value).into(), 4);
+        }
+        bits.bytes
+    }
+
+    pub fn decode<G>(&self, bytes: &[u8]) -> Result<G, PackedReadError>
+    where
+        G: GridMutWithDefault,
+    {
+        let mut grid = G::default();
+        self.read_from_bytes(bytes, &mut grid)?;
+        Ok(grid)
+    }
+}
+
+impl ReadFormatter for PackedPuzzle {
+    type ReadError = PackedReadError;
+
+    fn read<R, G>(&self, reader: &mut R, grid: &mut G) -> Result<(), Self::ReadError>
+    where
+        R: Read,
+        G: GridMut + ?Sized,
+    {
+        let mut bytes = vec![0; MASK_LEN];
+        read_record(reader, &mut bytes)?;
+        let mut bits = BitReader {
+            bytes: &bytes,
+            pos: 0,
+        };
+        let given = (0..MASK_BITS)
+            .map(|_| bits.take(1) == 1)
+            .collect::<Vec<_>>();
+        let len = Self::len(given.iter().filter(|x| **x).count());
+        bytes.resize(len, 0);
+        match read_record(reader, &mut bytes[MASK_LEN..]) {
+            Err(PackedReadError::EndOfInput) => return Err(PackedReadError::Truncated),
+            res => res?,
+        }
+        let mut bits = BitReader {
+            bytes: &bytes,
+            pos: MASK_BITS,
+        };
+        for (idx, given) in CellIdx::iter_row_wise().zip(given) {
+            grid[idx] = match given {
+                true => Some(
+                    Digit::try_from(bits.take(4) as u8).map_err(|_| PackedReadError::Invalid)?,
+                ),
+                false => None,
+            };
+        }
+        match bits.padding_is_zero() {
+            true => Ok(()),
+            false => Err(PackedReadError::Invalid),
+        }
+    }
+}
+
+impl WriteFormatter for PackedPuzzle {
+    fn write<G, W>(&self, grid: &G, writer: &mut W) -> std::io::Result<usize>
+    where
+        G: Grid + ?Sized,
+        W: Write,
+    {
+        let bytes = self.encode(grid);
+        writer.write_all(&bytes)?;
+        Ok(bytes.len())
+    }
+}
+
+/// Complete solution grids in `PackedSolution::LEN` bytes: the rank of each of the first 8 rows
+/// among the 9! permutations of the digits, in 19 bits each. The last row follows from the
+/// others, as each column misses a single digit.
+#[derive(Debug, Default)]
+pub struct PackedSolution;
+
+impl PackedSolution {
+    pub const LEN: usize = (DIM - 1) * RANK_BITS / 8;
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn rank(row: &[u8]) -> u32 {
+        let mut unused = (1u16 << DIM) - 1;
+        row.iter().enumerate().fold(0, |acc, (i, value)| {
+            let smaller = (unused & ((1 << value) - 1)).count_ones();
+            unused &= !(1 << value);
+            acc + smaller * FACTORIALS[DIM - 1 - i]
+        })
+    }
+
+    fn unrank(mut rank: u32) -> [u8; DIM] {
+        let mut unused = (0..DIM as u8).collect::<Vec<_>>();
+        std::array::from_fn(|i| {
+            let f = FACTORIALS[DIM - 1 - i];
+            let value = unused.remove((rank / f) as usize);
+            rank %= f;
+            value
+        })
+    }
+
+    /// Encodes `grid`, or returns `None` if it is not a complete solution.
+    pub fn encode<G>(&self, grid: &G) -> Option<[u8; Self::LEN]>
+    where
+        G: Grid + ?Sized,
+    {
+        if !matches!(eval_status(grid), Ok(SudokuStatus::Complete)) {
+            return None;
+        }
+        let mut bits = BitWriter::default();
+        for row in RowIdx::iter().take(DIM - 1) {
+            let values = ColIdx::iter()
+                .map(|col| u8::from(grid[(row, col).into()].unwrap()))
+                .collect::<Vec<_>>();
+            bits.push(Self::rank(&values), RANK_BITS);
+        }
+        bits.bytes.try_into().ok()
+    }
+
+    pub fn decode<G>(&self, bytes: &[u8; Self::LEN]) -> Result<G, PackedReadError>
+    where
+        G: GridMutWithDefault,
+    {
+        let mut grid = G::default();
+        self.read_from_bytes(bytes, &mut grid)?;
+        Ok(grid)
+    }
+}
+
+impl ReadFormatter for PackedSolution {
+    type ReadError = PackedReadError;
+
+    fn read<R, G>(&self, reader: &mut R, grid: &mut G) -> Result<(), Self::ReadError>
+    where
+        R: Read,
+        G: GridMut + ?Sized,
+    {
+        let mut bytes = [0; Self::LEN];
+        read_record(reader, &mut bytes)?;
+        let mut bits = BitReader {
+            bytes: &bytes,
+            pos: 0,
+        };
+        let mut missing = vec![(1u16 << DIM) - 1; DIM];
+        for row in RowIdx::iter().take(DIM - 1) {
+            let rank = bits.take(RANK_BITS);
+            if rank >= FACTORIALS[DIM - 1] * DIM as u32 {
+                return Err(PackedReadError::Invalid);
+            }
+            for ((col, value), missing) in ColIdx::iter().zip(Self::unrank(rank)).zip(&mut missing)
+            {
+                *missing &= !(1 << value);
+                grid[(row, col).into()] = Some(Digit::try_from(value).unwrap());
+            }
+        }
+        for (col, missing) in ColIdx::iter().zip(missing) {
+            if missing.count_ones() != 1 {
+                return Err(PackedReadError::Invalid);
+            }
+            let value = missing.trailing_zeros() as u8;
+            grid[(RowIdx::Row8, col).into()] = Some(Digit::try_from(value).unwrap());
+        }
+        match eval_status(grid) {
+            Ok(SudokuStatus::Complete) => Ok(()),
+            _ => Err(PackedReadError::Invalid),
+        }
+    }
+}
+
+impl WriteFormatter for PackedSolution {
+    /// Fails with `ErrorKind::InvalidInput` if `grid` is not a complete solution.
+    fn write<G, W>(&self, grid: &G, writer: &mut W) -> std::io::Result<usize>
+    where
+        G: Grid + ?Sized,
+        W: Write,
+    {
+        let bytes = self.encode(grid).ok_or_else(|| {
+            std::io::Error::new(ErrorKind::InvalidInput, "grid is not a complete solution")
+        })?;
+        writer.write_all(&bytes)?;
+        Ok(bytes.len())
+    }
+}
+
+/// Reads records with `f` until `reader` is exhausted.
+pub fn read_records<'a, F, R, G>(
+    f: &'a F,
+    reader: &'a mut R,
+) -> impl Iterator<Item = Result<G, PackedReadError>> + 'a
+where
+    F: ReadFormatter<ReadError = PackedReadError>,
+    R: Read,
+    G: GridMutWithDefault + 'a,
+{
+    std::iter::from_fn(move || {
+        let mut grid = G::default();
+        match f.read(reader, &mut grid) {
+            Ok(()) => Some(Ok(grid)),
+            Err(PackedReadError::EndOfInput) => None,
+            Err(e) => Some(Err(e)),
+        }
+    })
+}
+
+/// Writes a record of each grid with `f` and returns the number of bytes written.
+pub fn write_records<'a, F, G, I, W>(f: &F, grids: I, writer: &mut W) -> std::io::Result<usize>
+where
+    F: WriteFormatter,
+    G: Grid + ?Sized + 'a,
+    I: IntoIterator<Item = &'a G>,
+    W: Write,
+{
+    grids
+        .into_iter()
+        .try_fold(0, |res, grid| Ok(res + f.write(grid, writer)?))
+}
+
+#[cfg(test)]
+mod packed_test {
+    use super::{read_records, write_records, PackedPuzzle, PackedReadError, PackedSolution};
+    use crate::format::{read_from_string, ReadFormatter, RowMajorAscii};
+    use crate::grid::{ArrGridColMajor, ArrGridRowMajor, CellIdx, Digit};
+    use std::io::Cursor;
+
+    fn solution() -> ArrGridRowMajor {
+        let grid = r#"
+534678912
+672195348
+198342567
+859761423
+426853791
+713924856
+961537284
+287419635
+345286179
+"#
+        .trim();
+        read_from_string(&RowMajorAscii::default(), grid).unwrap()
+    }
+
+    fn puzzle() -> ArrGridRowMajor {
+        let grid = r#"
+53__7____
+6__195___
+_98____6_
+8___6___3
+4__8_3__1
+7___2___6
+_6____28_
+___419__5
+____8__79
+"#
+        .trim();
+        read_from_string(&RowMajorAscii::default(), grid).unwrap()
+    }
+
+    #[test]
+    fn test_puzzle() {
+        let f = PackedPuzzle::new();
+        let bytes = f.encode(&puzzle());
+        assert_eq!(PackedPuzzle::len(30), bytes.len());
+        assert_eq!(26, bytes.len());
+        assert_eq!(0b1100_1000, bytes[0]);
+        assert_eq!(puzzle(), f.decode::<ArrGridColMajor>(&bytes).unwrap());
+        assert_eq!(11, f.encode(&ArrGridRowMajor::new()).len());
+        assert_eq!(51, f.encode(&solution()).len());
+        assert_eq!(
+            solution(),
+            f.decode::<ArrGridRowMajor>(&f.encode(&solution())).unwrap()
+        );
+        // The mask bit of the last cell, then 5 stored as 4 and the start of 3 stored as 2.
+        assert_eq!(0b1010_0001, bytes[10]);
+        let mut invalid = bytes.clone();
+        invalid[10] |= 0b0111_1000;
+        assert_eq!(
+            Err(PackedReadError::Invalid),
+            f.decode::<ArrGridRowMajor>(&invalid)
+        );
+        let mut padded = bytes.clone();
+        *padded.last_mut().unwrap() |= 1;
+        assert_eq!(
+            Err(PackedReadError::Invalid),
+            f.decode::<ArrGridRowMajor>(&padded)
+        );
+        assert_eq!(
+            Err(PackedReadError::Truncated),
+            f.decode::<ArrGridRowMajor>(&bytes[..20])
+        );
+        assert_eq!(
+            Err(PackedReadError::EndOfInput),
+            f.decode::<ArrGridRowMajor>(&[])
+        );
+    }
+
+    #[test]
+    fn test_solution() {
+        let f = PackedSolution::new();
+        let bytes = f.encode(&solution()).unwrap();
+        assert_eq!(19, bytes.len());
+        assert_eq!(solution(), f.decode::<ArrGridColMajor>(&bytes).unwrap());
+        assert_eq!(None, f.encode(&puzzle()));
+        let mut swapped = solution();
+        let (a, b) = (
+            CellIdx::try_of_row_major(0).unwrap(),
+            CellIdx::try_of_row_major(1).unwrap(),
+        );
+        swapped[a] = Some(Digit::D3);
+        swapped[b] = Some(Digit::D5);
+        assert_eq!(None, f.encode(&swapped));
+        assert_eq!(
+            Err(PackedReadError::Invalid),
+            f.decode::<ArrGridRowMajor>(&[0xff; PackedSolution::LEN])
+        );
+        // Identity permutations in every row repeat digits in the columns.
+        assert_eq!(
+            Err(PackedReadError::Invalid),
+            f.decode::<ArrGridRowMajor>(&[0; PackedSolution::LEN])
+        );
+        let mut grid = ArrGridRowMajor::new();
+        assert_eq!(
+            Err(PackedReadError::Truncated),
+            f.read_from_bytes(&bytes[..18], &mut grid)
+        );
+    }
+
+    #[test]
+    fn test_records() {
+        let f = PackedPuzzle::new();
+        let grids = [puzzle(), ArrGridRowMajor::new(), solution()];
+        let mut cursor = Cursor::new(Vec::new());
+        assert_eq!(
+            26 + 11 + 51,
+            write_records(&f, &grids, &mut cursor).unwrap()
+        );
+        let bytes = cursor.into_inner();
+        let read = read_records(&f, &mut bytes.as_slice())
+            .collect::<Result<Vec<ArrGridRowMajor>, _>>()
+            .unwrap();
+        assert_eq!(grids.to_vec(), read);
+        let mut truncated = &bytes[..bytes.len() - 1];
+        let read = read_records::<_, _, ArrGridRowMajor>(&f, &mut truncated).collect::<Vec<_>>();
+        assert_eq!(3, read.len());
+        assert_eq!(Some(&Err(PackedReadError::Truncated)), read.last());
+
+        let f = PackedSolution::new();
+        let mut cursor = Cursor::new(Vec::new());
+        assert_eq!(
+            38,
+            write_records(&f, [&solution(); 2], &mut cursor).unwrap()
+        );
+        let bytes = cursor.into_inner();
+        let mut reader = bytes.as_slice();
+        let read = read_records::<_, _, ArrGridRowMajor>(&f, &mut reader).collect::<Vec<_>>();
+        assert_eq!(vec![Ok(solution()), Ok(solution())], read);
+        let err = write_records(&f, [&puzzle()], &mut Cursor::new(Vec::new())).unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+    }
+}
@@ -1,24 +1,132 @@
 use super::{ReadFormatter, WriteFormatter};
-use crate::grid::{Grid, CellIdx, GridMut, Digit, RowIdx, ColIdx};
-use std::io::{Read, Write};
+use crate::grid::{ArrGridRowMajor, CellIdx, Grid, GridMut, Shape, Shape9x9, SizedCellIdx};
+use std::fmt::{Display, Formatter};
+use std::io::{Cursor, ErrorKind, Read, Write};
+use std::marker::PhantomData;
 use std::slice;
-use strum::EnumCount;
+use thiserror::Error;
 
+/// Symbols of digits 1 to 32, of which a grid uses the first `Shape::DIM`.
+const DEFAULT_SYMBOLS: &str = "123456789ABCDEFGHIJKLMNOPQRSTUVW";
+
+/// Place in the input, with lines and columns numbered from 1.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TextPosition {
+    pub offset: usize,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Display for TextPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {} (byte {})",
+            self.line, self.col, self.offset
+        )
+    }
+}
+
+/// What `RowMajorAscii` reads next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
+    /// The symbol of a digit or the empty cell marker.
+    Cell {
+        empty_cell: char,
+    },
+    RowSep(char),
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cell { empty_cell } => write!(f, "a digit or {:?}", empty_cell),
+            Self::RowSep(row_sep) => write!(f, "row separator {:?}", row_sep),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, Eq, PartialEq)]
+pub enum RowMajorAsciiReadError {
+    #[error("Reading a grid fails")]
+    Io,
+    #[error("unexpected {found:?} at {position}, expected {expected}")]
+    Unexpected {
+        position: TextPosition,
+        found: char,
+        expected: Expected,
+    },
+    #[error("input ends at {position} before the grid does, expected {expected}")]
+    EndOfInput {
+        position: TextPosition,
+        expected: Expected,
+    },
+}
+
+impl RowMajorAsciiReadError {
+    pub fn position(&self) -> Option<TextPosition> {
+        match self {
+            Self::Io => None,
+            Self::Unexpected { position, .. } | Self::EndOfInput { position, .. } => {
+                Some(*position)
+            }
+        }
+    }
+
+    /// Whether the input ended before the last cell.
+    pub fn is_end_of_input(&self) -> bool {
+        matches!(self, Self::EndOfInput { .. })
+    }
+}
+
+#[derive(Debug, Error, Clone, Eq, PartialEq)]
+pub enum RowMajorAsciiSymbolsError {
+    #[error("{0:?} is not a printable ASCII character")]
+    NotAscii(char),
+    #[error("{0:?} stands for more than one digit, or for a digit and an empty cell or row end")]
+    Ambiguous(char),
+}
+
+/// Character starting with byte `lead`, reading the rest of it from `reader` if not ASCII.
+fn char_of<R>(reader: &mut R, lead: u8) -> char
+where
+    R: Read,
+{
+    let len = match lead {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    };
+    let mut buf = [lead, 0, 0, 0];
+    if len > 1 && reader.read_exact(&mut buf[1..len]).is_err() {
+        return char::REPLACEMENT_CHARACTER;
+    }
+    std::str::from_utf8(&buf[..len])
+        .ok()
+        .and_then(|s| s.chars().next())
+        .unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+/// Row-major text format, one symbol per cell. Digits are written `1` to `9`, then `A` to `W` on
+/// grids of more than 9 digits, unless other symbols are given.
 #[derive(Debug)]
-pub struct RowMajorAscii {
+pub struct RowMajorAscii<S: Shape = Shape9x9> {
+    symbols: Vec<u8>,
     empty_cell: u8,
     row_sep: Option<u8>,
+    shape: PhantomData<S>,
 }
 
 #[derive(Debug)]
-struct RowMajorAsciiReadState<'a> {
+struct RowMajorAsciiReadState<'a, S: Shape> {
     row_sep_expected: bool,
     row_major_idx: usize,
-    formatter: &'a RowMajorAscii,
+    formatter: &'a RowMajorAscii<S>,
 }
 
-impl<'a> RowMajorAsciiReadState<'a> {
-    fn new<'b>(formatter: &'a RowMajorAscii) -> Self
+impl<'a, S: Shape> RowMajorAsciiReadState<'a, S> {
+    fn new<'b>(formatter: &'a RowMajorAscii<S>) -> Self
     where
         'b: 'a,
     {
@@ -30,8 +138,8 @@ impl<'a> RowMajorAsciiReadState<'a> {
     }
 
     fn inc(&mut self) {
-        let idx = CellIdx::try_of_row_major(self.row_major_idx).unwrap();
-        if idx.row < RowIdx::Row8 && idx.col == ColIdx::Col8 {
+        let idx = SizedCellIdx::of_row_major::<S>(self.row_major_idx);
+        if idx.row + 1 < S::DIM && idx.col + 1 == S::DIM {
             self.row_sep_expected = self.formatter.row_sep.is_some();
         };
         self.row_major_idx += 1;
@@ -46,29 +154,184 @@ impl<'a> RowMajorAsciiReadState<'a> {
     }
 
     fn is_done(&self) -> bool {
-        self.row_major_idx >= RowIdx::COUNT * ColIdx::COUNT
+        self.row_major_idx >= S::COUNT
     }
 }
 
 impl ReadFormatter for RowMajorAscii {
-    // TODO(kostya): better error type
-    type ReadError = ();
+    type ReadError = RowMajorAsciiReadError;
 
     fn read<R, G>(&self, reader: &mut R, grid: &mut G) -> Result<(), Self::ReadError>
     where
         R: Read,
         G: GridMut + ?Sized,
+    {
+        self.read_cells(reader, |idx, value| {
+            grid[CellIdx::try_of_row_major(idx).unwrap()] = value.map(Shape9x9::digit)
+        })
+    }
+}
+
+impl WriteFormatter for RowMajorAscii {
+    fn write<G, W>(&self, grid: &G, writer: &mut W) -> std::io::Result<usize>
+    where
+        G: Grid + ?Sized,
+        W: Write,
+    {
+        self.write_cells(
+            CellIdx::iter_row_wise().map(|idx| grid[idx].map(u8::from)),
+            writer,
+        )
+    }
+}
+
+impl<S: Shape> Default for RowMajorAscii<S> {
+    fn default() -> Self {
+        Self {
+            symbols: DEFAULT_SYMBOLS.as_bytes().to_vec(),
+            empty_cell: b'_',
+            row_sep: Some(b'\n'),
+            shape: PhantomData,
+        }
+    }
+}
+
+impl RowMajorAscii {
+    pub fn new(empty_cell: Option<char>, row_sep: Option<Option<char>>) -> Self {
+        let empty_cell: u8 = empty_cell.unwrap_or('_').try_into().unwrap();
+        let row_sep: Option<u8> = row_sep.unwrap_or(Some('\n')).map(|x| x.try_into().unwrap());
+        Self {
+            empty_cell,
+            row_sep,
+            ..Default::default()
+        }
+    }
+}
+
+impl<S: Shape> RowMajorAscii<S> {
+    /// Writes digit `d` as the `d`-th of `symbols`, which defaults to `1` to `9` then `A` to
+    /// `W`. Fails unless the symbols and `empty_cell` are distinct printable ASCII characters
+    /// and `row_sep` is none of them.
+    pub fn with_symbols(
+        symbols: Option<&str>,
+        empty_cell: Option<char>,
+        row_sep: Option<Option<char>>,
+    ) -> Result<Self, RowMajorAsciiSymbolsError> {
+        let ascii = |c: char, graphic: bool| match u8::try_from(c) {
+            Ok(x) if x.is_ascii_graphic() || (!graphic && x.is_ascii_whitespace()) => Ok(x),
+            _ => Err(RowMajorAsciiSymbolsError::NotAscii(c)),
+        };
+        let symbols = symbols
+            .unwrap_or(DEFAULT_SYMBOLS)
+            .chars()
+            .map(|c| ascii(c, true))
+            .collect::<Result<Vec<_>, _>>()?;
+        let empty_cell = ascii(empty_cell.unwrap_or('_'), true)?;
+        let row_sep = row_sep
+            .unwrap_or(Some('\n'))
+            .map(|c| ascii(c, false))
+            .transpose()?;
+        let mut seen = Vec::with_capacity(symbols.len() + 2);
+        for c in symbols.iter().chain([&empty_cell]).chain(&row_sep) {
+            if seen.contains(c) {
+                return Err(RowMajorAsciiSymbolsError::Ambiguous((*c).into()));
+            }
+            seen.push(*c);
+        }
+        Ok(Self {
+            symbols,
+            empty_cell,
+            row_sep,
+            shape: PhantomData,
+        })
+    }
+
+    pub fn read_sized<R>(
+        &self,
+        reader: &mut R,
+    ) -> Result<ArrGridRowMajor<S>, RowMajorAsciiReadError>
+    where
+        R: Read,
+    {
+        let mut grid = ArrGridRowMajor::<S>::default();
+        self.read_cells(reader, |idx, value| {
+            grid[SizedCellIdx::of_row_major::<S>(idx)] = value.map(S::digit)
+        })?;
+        Ok(grid)
+    }
+
+    pub fn read_sized_from_string(
+        &self,
+        s: &str,
+    ) -> Result<ArrGridRowMajor<S>, RowMajorAsciiReadError> {
+        self.read_sized(&mut Cursor::new(s.as_bytes()))
+    }
+
+    /// Fails with `ErrorKind::InvalidInput` if `grid` holds a digit without a symbol.
+    pub fn write_sized<W>(
+        &self,
+        grid: &ArrGridRowMajor<S>,
+        writer: &mut W,
+    ) -> std::io::Result<usize>
+    where
+        W: Write,
+    {
+        self.write_cells(
+            S::iter_row_wise().map(|idx| grid[idx].map(Into::into)),
+            writer,
+        )
+    }
+
+    pub fn write_sized_string(&self, grid: &ArrGridRowMajor<S>) -> std::io::Result<String> {
+        let mut cursor = Cursor::new(Vec::with_capacity(S::COUNT + S::DIM - 1));
+        self.write_sized(grid, &mut cursor)?;
+        Ok(String::from_utf8(cursor.into_inner()).unwrap())
+    }
+
+    /// Reads `S::COUNT` cells, passing each one's row-major index and zero-based digit to `set`.
+    fn read_cells<R, F>(&self, reader: &mut R, mut set: F) -> Result<(), RowMajorAsciiReadError>
+    where
+        R: Read,
+        F: FnMut(usize, Option<u8>),
     {
         let mut state = RowMajorAsciiReadState::new(self);
-        let is_cell = |c: u8| c.is_ascii_digit() && c != b'0';
+        let symbols = &self.symbols[..S::DIM.min(self.symbols.len())];
+        let digit = |c: u8| symbols.iter().position(|x| *x == c).map(|x| x as u8);
         let is_empty = |c: u8| c == self.empty_cell;
         let is_row_sep = |c: u8| self.row_sep == Some(c);
+        let mut next = TextPosition {
+            offset: 0,
+            line: 1,
+            col: 1,
+        };
         loop {
-            let idx = CellIdx::try_of_row_major(state.row_major_idx).unwrap();
+            let idx = state.row_major_idx;
+            let expected = match self.row_sep {
+                Some(row_sep) if state.is_row_sep_expected() => Expected::RowSep(row_sep.into()),
+                _ => Expected::Cell {
+                    empty_cell: self.empty_cell.into(),
+                },
+            };
+            let position = next;
             let mut c = 0;
             match reader.read_exact(slice::from_mut(&mut c)) {
-                Err(_) => return Err(()),
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => {
+                    return Err(RowMajorAsciiReadError::EndOfInput { position, expected })
+                }
+                Err(_) => return Err(RowMajorAsciiReadError::Io),
                 Ok(()) => {
+                    next.offset += 1;
+                    if c == b'\n' {
+                        next.line += 1;
+                        next.col = 1;
+                    } else {
+                        next.col += 1;
+                    }
+                    let unexpected = |reader: &mut R| RowMajorAsciiReadError::Unexpected {
+                        position,
+                        found: char_of(reader, c),
+                        expected,
+                    };
                     if state.is_row_sep_expected() {
                         if is_row_sep(c) {
                             state.saw_row_sep();
@@ -76,18 +339,18 @@ impl ReadFormatter for RowMajorAscii {
                         } else if c.is_ascii_whitespace() {
                             continue;
                         } else {
-                            return Err(());
+                            return Err(unexpected(reader));
                         }
                     } else if c.is_ascii_whitespace() {
                         continue;
-                    } else if is_cell(c) {
-                        grid[idx] = Some(Digit::try_from_ascii(c).unwrap());
+                    } else if let Some(value) = digit(c) {
+                        set(idx, Some(value));
                         state.inc();
                     } else if is_empty(c) {
-                        grid[idx] = None;
+                        set(idx, None);
                         state.inc();
                     } else {
-                        return Err(());
+                        return Err(unexpected(reader));
                     }
                 }
             }
@@ -97,18 +360,26 @@ impl ReadFormatter for RowMajorAscii {
             }
         }
     }
-}
 
-impl WriteFormatter for RowMajorAscii {
-    fn write<G, W>(&self, grid: &G, writer: &mut W) -> std::io::Result<usize>
+    /// Writes `S::COUNT` zero-based digits in row-major order.
+    fn write_cells<I, W>(&self, cells: I, writer: &mut W) -> std::io::Result<usize>
     where
-        G: Grid + ?Sized,
+        I: Iterator<Item = Option<u8>>,
         W: Write,
     {
-        CellIdx::iter_row_wise().try_fold(0, |res, idx| {
-            let cell = grid[idx].map(|x| x.as_ascii()).unwrap_or(self.empty_cell);
+        cells.enumerate().try_fold(0, |res, (idx, value)| {
+            let cell = match value {
+                Some(x) => *self.symbols.get(usize::from(x)).ok_or_else(|| {
+                    std::io::Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("no symbol for digit {}", x + 1),
+                    )
+                })?,
+                None => self.empty_cell,
+            };
             let cell = writer.write(slice::from_ref(&cell))?;
-            let row_sep = if idx.row != RowIdx::Row8 && idx.col == ColIdx::Col8 {
+            let idx = SizedCellIdx::of_row_major::<S>(idx);
+            let row_sep = if idx.row + 1 != S::DIM && idx.col + 1 == S::DIM {
                 self.row_sep
                     .map_or(Ok(0), |x| writer.write(slice::from_ref(&x)))
             } else {
@@ -119,28 +390,16 @@ impl WriteFormatter for RowMajorAscii {
     }
 }
 
-impl Default for RowMajorAscii {
-    fn default() -> Self {
-        Self::new(None, None)
-    }
-}
-
-impl RowMajorAscii {
-    pub fn new(empty_cell: Option<char>, row_sep: Option<Option<char>>) -> Self {
-        let empty_cell: u8 = empty_cell.unwrap_or('_').try_into().unwrap();
-        let row_sep: Option<u8> = row_sep.unwrap_or(Some('\n')).map(|x| x.try_into().unwrap());
-        Self {
-            empty_cell,
-            row_sep,
-        }
-    }
-}
-
 #[cfg(test)]
 mod row_major_ascii_test {
     use super::super::{read_from_string, write_string, ReadFormatter, WriteFormatter};
-    use super::RowMajorAscii;
-    use crate::grid::{ArrGridRowMajor, Grid, GridMutWithDefault};
+    use super::{
+        Expected, RowMajorAscii, RowMajorAsciiReadError, RowMajorAsciiSymbolsError, TextPosition,
+    };
+    use crate::grid::{
+        ArrGridRowMajor, Grid, GridMutWithDefault, Shape16x16, Shape4x4, Shape6x6, SizedCellIdx,
+    };
+    use std::io::ErrorKind;
 
     fn grid_roundtrip<F, Src, Dst>(f: &F, src: &Src) -> Dst
     where
@@ -205,7 +464,135 @@ ____8__79
 "#
         .trim();
         let f = RowMajorAscii::default();
-        let actual = str_roundtrip(&f, &expected);
+        let actual = str_roundtrip(&f, expected);
         assert_eq!(&expected, &actual);
     }
+
+    #[test]
+    fn test_errors() {
+        let f = RowMajorAscii::default();
+        let read = |s: &str| read_from_string::<_, ArrGridRowMajor>(&f, s).unwrap_err();
+        let rows = ["_________"; 9].join("\n");
+        let cell = Expected::Cell { empty_cell: '_' };
+        let at = |offset, line, col| TextPosition { offset, line, col };
+
+        let err = read(&rows.replacen("_", "x", 1));
+        assert_eq!(
+            RowMajorAsciiReadError::Unexpected {
+                position: at(0, 1, 1),
+                found: 'x',
+                expected: cell,
+            },
+            err
+        );
+        assert_eq!(
+            "unexpected 'x' at line 1, column 1 (byte 0), expected a digit or '_'",
+            err.to_string()
+        );
+        // Non-ASCII characters are reported whole.
+        assert_eq!(
+            RowMajorAsciiReadError::Unexpected {
+                position: at(13, 2, 4),
+                found: '│',
+                expected: cell,
+            },
+            read(&rows.replacen("\n___", "\n___│", 1))
+        );
+        assert_eq!(
+            RowMajorAsciiReadError::Unexpected {
+                position: at(9, 1, 10),
+                found: '_',
+                expected: Expected::RowSep('\n'),
+            },
+            read(&rows.replacen("\n", "_\n", 1))
+        );
+        let err = read(&rows[..85]);
+        assert!(err.is_end_of_input());
+        assert_eq!(
+            RowMajorAsciiReadError::EndOfInput {
+                position: at(85, 9, 6),
+                expected: cell,
+            },
+            err
+        );
+        assert_eq!(Some(at(85, 9, 6)), err.position());
+        assert_eq!(
+            RowMajorAsciiReadError::EndOfInput {
+                position: at(9, 1, 10),
+                expected: Expected::RowSep('\n'),
+            },
+            read("_________")
+        );
+    }
+
+    #[test]
+    fn test_sized_roundtrip() {
+        let expected = r#"
+1_3___
+___2__
+__6___
+_4____
+____5_
+2_____
+"#
+        .trim();
+        let f = RowMajorAscii::<Shape6x6>::default();
+        let grid = f.read_sized_from_string(expected).unwrap();
+        assert_eq!(Some(5), grid[SizedCellIdx { row: 2, col: 2 }]);
+        assert_eq!(expected, f.write_sized_string(&grid).unwrap());
+    }
+
+    #[test]
+    fn test_symbols() {
+        let f = RowMajorAscii::<Shape16x16>::default();
+        let mut grid = ArrGridRowMajor::<Shape16x16>::default();
+        (0..16).for_each(|col| grid[SizedCellIdx { row: 0, col }] = Some(15 - col as u8));
+        let s = f.write_sized_string(&grid).unwrap();
+        assert_eq!("GFEDCBA987654321", s.lines().next().unwrap());
+        assert_eq!(grid, f.read_sized_from_string(&s).unwrap());
+
+        let f = RowMajorAscii::with_symbols(Some("0123456789ABCDEF"), Some('.'), None).unwrap();
+        let s = f.write_sized_string(&grid).unwrap();
+        assert_eq!("FEDCBA9876543210", &s[..16]);
+        assert_eq!(grid, f.read_sized_from_string(&s).unwrap());
+
+        let f = RowMajorAscii::<Shape16x16>::with_symbols(Some("123456789"), None, None).unwrap();
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            f.write_sized_string(&grid).unwrap_err().kind()
+        );
+        assert_eq!(
+            Err(RowMajorAsciiSymbolsError::Ambiguous('1')),
+            RowMajorAscii::<Shape4x4>::with_symbols(Some("1231"), None, None).map(|_| ())
+        );
+        assert_eq!(
+            Err(RowMajorAsciiSymbolsError::Ambiguous('_')),
+            RowMajorAscii::<Shape4x4>::with_symbols(Some("123_"), None, None).map(|_| ())
+        );
+        assert_eq!(
+            Err(RowMajorAsciiSymbolsError::NotAscii('│')),
+            RowMajorAscii::<Shape4x4>::with_symbols(None, Some('│'), None).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_sized_errors() {
+        let f = RowMajorAscii::<Shape6x6>::default();
+        // 7 is not a digit of a 6x6 grid.
+        assert!(matches!(
+            f.read_sized_from_string("7"),
+            Err(RowMajorAsciiReadError::Unexpected { found: '7', .. })
+        ));
+        assert!(f
+            .read_sized_from_string("123456")
+            .unwrap_err()
+            .is_end_of_input());
+        assert_eq!(
+            Some(Expected::RowSep('\n')),
+            match f.read_sized_from_string("1234561") {
+                Err(RowMajorAsciiReadError::Unexpected { expected, .. }) => Some(expected),
+                _ => None,
+            }
+        );
+    }
 }
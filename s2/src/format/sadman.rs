@@ -0,0 +1,541 @@
+use super::{ReadFormatter, WriteFormatter};
+use crate::grid::{ArrGridRowMajor, CellIdx, Digit, Grid, GridMut, GridMutWithDefault};
+use std::io::{Cursor, Read, Write};
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum PuzzleFileReadError {
+    #[error("Reading a puzzle file fails")]
+    Io,
+    #[error("line {line} does not fit the file format")]
+    Syntax { line: usize },
+}
+
+/// Metadata from the `#` header lines of SadMan files, each line a letter and a value such as
+/// `#A Jane Doe`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PuzzleInfo {
+    /// `#A`
+    pub author: Option<String>,
+    /// `#D`
+    pub description: Option<String>,
+    /// `#C`
+    pub comment: Option<String>,
+    /// `#B`
+    pub date: Option<String>,
+    /// `#S`
+    pub source: Option<String>,
+    /// `#L`, such as `Easy` or `Diabolical`.
+    pub difficulty: Option<String>,
+    /// `#U`
+    pub url: Option<String>,
+}
+
+impl PuzzleInfo {
+    fn fields(&self) -> [(char, &Option<String>); 7] {
+        [
+            ('A', &self.author),
+            ('D', &self.description),
+            ('C', &self.comment),
+            ('B', &self.date),
+            ('S', &self.source),
+            ('L', &self.difficulty),
+            ('U', &self.url),
+        ]
+    }
+
+    fn field_mut(&mut self, key: char) -> Option<&mut Option<String>> {
+        match key {
+            'A' => Some(&mut self.author),
+            'D' => Some(&mut self.description),
+            'C' => Some(&mut self.comment),
+            'B' => Some(&mut self.date),
+            'S' => Some(&mut self.source),
+            'L' => Some(&mut self.difficulty),
+            'U' => Some(&mut self.url),
+            _ => None,
+        }
+    }
+
+    fn write(&self, s: &mut String) {
+        for (key, value) in self.fields() {
+            if let Some(value) = value {
+                s.push_str(&format!("#{key} {value}\n"));
+            }
+        }
+    }
+}
+
+/// Puzzle as desktop tools save it: the givens, and how far solving got.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PuzzleFile {
+    pub info: PuzzleInfo,
+    pub givens: ArrGridRowMajor,
+    /// Digits placed while solving, givens aside.
+    pub placed: ArrGridRowMajor,
+    /// Candidates of each cell in row-major order, one bit per digit, empty if not kept.
+    pub candidates: Vec<u16>,
+}
+
+/// Lines of `s` without line endings, numbered from 1, header lines parsed into `info`. Blank
+/// lines are skipped.
+fn body(s: &str, info: &mut PuzzleInfo) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    for (i, text) in s.lines().enumerate() {
+        let text = text.trim();
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (None, _) => (),
+            (Some('#'), Some(key)) => {
+                if let Some(field) = info.field_mut(key) {
+                    *field = Some(chars.as_str().trim().to_string());
+                }
+            }
+            _ => lines.push((i + 1, text.to_string())),
+        }
+    }
+    lines
+}
+
+fn read_string<R>(reader: &mut R) -> Result<String, PuzzleFileReadError>
+where
+    R: Read,
+{
+    let mut s = String::new();
+    reader
+        .read_to_string(&mut s)
+        .map_err(|_| PuzzleFileReadError::Io)?;
+    Ok(s)
+}
+
+/// Digits of 9 rows of 9 characters, `.` or `0` for empty cells.
+fn read_rows(
+    rows: &[(usize, String)],
+    grid: &mut ArrGridRowMajor,
+) -> Result<(), PuzzleFileReadError> {
+    let syntax = |line| PuzzleFileReadError::Syntax { line };
+    let last = rows.last().map_or(0, |(line, _)| *line);
+    if rows.len() != 9 {
+        return Err(syntax(last + 1));
+    }
+    let cells = CellIdx::iter_row_wise().collect::<Vec<_>>();
+    for ((line, text), cells) in rows.iter().zip(cells.chunks(9)) {
+        let text = text.as_bytes();
+        if text.len() != 9 {
+            return Err(syntax(*line));
+        }
+        for (c, idx) in text.iter().zip(cells) {
+            grid[*idx] = match c {
+                b'.' | b'0' => None,
+                b'1'..=b'9' => Some(Digit::try_from_ascii(*c).unwrap()),
+                _ => return Err(syntax(*line)),
+            };
+        }
+    }
+    Ok(())
+}
+
+fn write_rows<G>(grid: &G, s: &mut String)
+where
+    G: Grid + ?Sized,
+{
+    for (idx, value) in grid.iter_row_wise() {
+        s.push(value.map_or('.', |x| x.as_ascii().into()));
+        if usize::from(idx.col) == 8 {
+            s.push('\n');
+        }
+    }
+}
+
+fn write_all<W>(s: &str, writer: &mut W) -> std::io::Result<usize>
+where
+    W: Write,
+{
+    writer.write_all(s.as_bytes())?;
+    Ok(s.len())
+}
+
+/// SadMan Software's .sdk files: header lines, then the givens under `[Puzzle]` as 9 lines of 9
+/// characters with `.` for empty cells, then optionally the grid as far as solved under
+/// `[State]`. A file with the 9 lines alone reads as well.
+#[derive(Debug, Default)]
+pub struct SadManSdk;
+
+impl SadManSdk {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn read_file<R>(&self, reader: &mut R) -> Result<PuzzleFile, PuzzleFileReadError>
+    where
+        R: Read,
+    {
+        let s = read_string(reader)?;
+        let mut file = PuzzleFile::default();
+        let lines = body(&s, &mut file.info);
+        let section = |name: &str| {
+            lines
+                .iter()
+                .position(|(_, text)| text.eq_ignore_ascii_case(name))
+        };
+        let (puzzle, state) = match (section("[Puzzle]"), section("[State]")) {
+            (None, None) => (&lines[..], None),
+            (Some(0), None) => (&lines[1..], None),
+            (Some(0), Some(state)) if state > 0 => (&lines[1..state], Some(&lines[state + 1..])),
+            _ => return Err(PuzzleFileReadError::Syntax { line: lines[0].0 }),
+        };
+        read_rows(puzzle, &mut file.givens)?;
+        if let Some(state) = state {
+            let mut grid = ArrGridRowMajor::new();
+            read_rows(state, &mut grid)?;
+            for (idx, value) in grid.iter_row_wise() {
+                match (file.givens[idx], value) {
+                    (Some(given), Some(value)) if given == value => (),
+                    (None, value) => file.placed[idx] = value,
+                    _ => {
+                        let line = state[usize::from(idx.row)].0;
+                        return Err(PuzzleFileReadError::Syntax { line });
+                    }
+                }
+            }
+        }
+        Ok(file)
+    }
+
+    pub fn read_file_from_string(&self, s: &str) -> Result<PuzzleFile, PuzzleFileReadError> {
+        self.read_file(&mut Cursor::new(s.as_bytes()))
+    }
+
+    /// Writes `[State]` only if some digit was placed. Candidates are left out.
+    pub fn write_file<W>(&self, file: &PuzzleFile, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: Write,
+    {
+        let mut s = String::new();
+        file.info.write(&mut s);
+        s.push_str("[Puzzle]\n");
+        write_rows(&file.givens, &mut s);
+        if file.placed.iter_set().next().is_some() {
+            s.push_str("[State]\n");
+            let mut state = file.givens;
+            state.set_from_iter(file.placed.iter_set());
+            write_rows(&state, &mut s);
+        }
+        write_all(&s, writer)
+    }
+
+    pub fn write_file_string(&self, file: &PuzzleFile) -> String {
+        let mut cursor = Cursor::new(Vec::new());
+        self.write_file(file, &mut cursor).unwrap();
+        String::from_utf8(cursor.into_inner()).unwrap()
+    }
+}
+
+/// SadMan Software's .sdx files: header lines, then 9 lines of 9 cells separated by blanks. A
+/// given is its digit alone, a digit placed while solving has a `u` in front, and an empty cell
+/// lists its candidates, with a `c` in front if there is only one so it does not read as a given.
+///
+/// ```text
+/// 5 u3 1248 c6 ...
+/// ```
+#[derive(Debug, Default)]
+pub struct SadManSdx;
+
+impl SadManSdx {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn read_file<R>(&self, reader: &mut R) -> Result<PuzzleFile, PuzzleFileReadError>
+    where
+        R: Read,
+    {
+        let s = read_string(reader)?;
+        let mut file = PuzzleFile {
+            candidates: vec![0; CellIdx::COUNT],
+            ..Default::default()
+        };
+        let mut lines = body(&s, &mut file.info);
+        if lines
+            .first()
+            .is_some_and(|(_, text)| text.eq_ignore_ascii_case("[Puzzle]"))
+        {
+            lines.remove(0);
+        }
+        let last = lines.last().map_or(0, |(line, _)| *line);
+        if lines.len() != 9 {
+            return Err(PuzzleFileReadError::Syntax { line: last + 1 });
+        }
+        let cells = CellIdx::iter_row_wise().collect::<Vec<_>>();
+        for ((line, text), cells) in lines.iter().zip(cells.chunks(9)) {
+            let syntax = || PuzzleFileReadError::Syntax { line: *line };
+            let tokens = text.split_whitespace().collect::<Vec<_>>();
+            if tokens.len() != 9 {
+                return Err(syntax());
+            }
+            for (token, idx) in tokens.into_iter().zip(cells) {
+                let (prefix, digits) = match token.as_bytes() {
+                    [prefix @ (b'u' | b'c'), digits @ ..] => (Some(*prefix), digits),
+                    digits => (None, digits),
+                };
+                if digits.is_empty() || !digits.iter().all(|c| (b'1'..=b'9').contains(c)) {
+                    return Err(syntax());
+                }
+                let mask = digits.iter().fold(0, |acc, c| acc | 1 << (c - b'1'));
+                let value = Digit::try_from_ascii(digits[0]).unwrap();
+                match (prefix, digits.len()) {
+                    (None, 1) => file.givens[*idx] = Some(value),
+                    (Some(b'u'), 1) => file.placed[*idx] = Some(value),
+                    (Some(b'c'), 1) | (None, _) => file.candidates[idx.row_major()] = mask,
+                    _ => return Err(syntax()),
+                }
+            }
+        }
+        Ok(file)
+    }
+
+    pub fn read_file_from_string(&self, s: &str) -> Result<PuzzleFile, PuzzleFileReadError> {
+        self.read_file(&mut Cursor::new(s.as_bytes()))
+    }
+
+    /// Writes every digit as a candidate of empty cells with none kept in `file`.
+    pub fn write_file<W>(&self, file: &PuzzleFile, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: Write,
+    {
+        let mut s = String::new();
+        file.info.write(&mut s);
+        for idx in CellIdx::iter_row_wise() {
+            let token = match (file.givens[idx], file.placed[idx]) {
+                (Some(value), _) => char::from(value.as_ascii()).to_string(),
+                (None, Some(value)) => format!("u{}", char::from(value.as_ascii())),
+                (None, None) => {
+                    let mask = match file.candidates.get(idx.row_major()) {
+                        None | Some(0) => (1 << 9) - 1,
+                        Some(mask) => *mask,
+                    };
+                    let digits = (0..9)
+                        .filter(|value| mask & (1 << value) != 0)
+                        .map(|value| char::from(b'1' + value))
+                        .collect::<String>();
+                    match digits.len() {
+                        1 => format!("c{digits}"),
+                        _ => digits,
+                    }
+                }
+            };
+            s.push_str(&token);
+            s.push(if usize::from(idx.col) == 8 { '\n' } else { ' ' });
+        }
+        write_all(&s, writer)
+    }
+
+    pub fn write_file_string(&self, file: &PuzzleFile) -> String {
+        let mut cursor = Cursor::new(Vec::new());
+        self.write_file(file, &mut cursor).unwrap();
+        String::from_utf8(cursor.into_inner()).unwrap()
+    }
+}
+
+/// Givens of `file` into `grid`, for reading files as grids.
+pub(super) fn read_givens<G>(file: &PuzzleFile, grid: &mut G)
+where
+    G: GridMut + ?Sized,
+{
+    for idx in CellIdx::iter_row_wise() {
+        grid[idx] = file.givens[idx];
+    }
+}
+
+/// File with `grid` as givens and nothing else, for writing grids as files.
+pub(super) fn of_givens<G>(grid: &G) -> PuzzleFile
+where
+    G: Grid + ?Sized,
+{
+    PuzzleFile {
+        givens: ArrGridRowMajor::copy_of(grid),
+        ..Default::default()
+    }
+}
+
+/// As a grid, a file reads as its givens.
+impl ReadFormatter for SadManSdk {
+    type ReadError = PuzzleFileReadError;
+
+    fn read<R, G>(&self, reader: &mut R, grid: &mut G) -> Result<(), Self::ReadError>
+    where
+        R: Read,
+        G: GridMut + ?Sized,
+    {
+        read_givens(&self.read_file(reader)?, grid);
+        Ok(())
+    }
+}
+
+impl WriteFormatter for SadManSdk {
+    fn write<G, W>(&self, grid: &G, writer: &mut W) -> std::io::Result<usize>
+    where
+        G: Grid + ?Sized,
+        W: Write,
+    {
+        self.write_file(&of_givens(grid), writer)
+    }
+}
+
+/// As a grid, a file reads as its givens.
+impl ReadFormatter for SadManSdx {
+    type ReadError = PuzzleFileReadError;
+
+    fn read<R, G>(&self, reader: &mut R, grid: &mut G) -> Result<(), Self::ReadError>
+    where
+        R: Read,
+        G: GridMut + ?Sized,
+    {
+        read_givens(&self.read_file(reader)?, grid);
+        Ok(())
+    }
+}
+
+impl WriteFormatter for SadManSdx {
+    fn write<G, W>(&self, grid: &G, writer: &mut W) -> std::io::Result<usize>
+    where
+        G: Grid + ?Sized,
+        W: Write,
+    {
+        self.write_file(&of_givens(grid), writer)
+    }
+}
+
+#[cfg(test)]
+mod sadman_test {
+    use super::{PuzzleFile, PuzzleFileReadError, PuzzleInfo, SadManSdk, SadManSdx};
+    use crate::format::{read_from_string, write_string, RowMajorAscii};
+    use crate::grid::{ArrGridRowMajor, CellIdx, Digit, Grid};
+
+    fn idx(x: usize) -> CellIdx {
+        CellIdx::try_of_row_major(x).unwrap()
+    }
+
+    fn givens() -> ArrGridRowMajor {
+        read_from_string(
+            &RowMajorAscii::default(),
+            "53__7____\n6__195___\n_98____6_\n8___6___3\n4__8_3__1\n7___2___6\n_6____28_\n___419__5\n____8__79",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sdk() {
+        let expected = r#"
+#A Jane Doe
+#S Daily paper
+#L Easy
+[Puzzle]
+53..7....
+6..195...
+.98....6.
+8...6...3
+4..8.3..1
+7...2...6
+.6....28.
+...419..5
+....8..79
+[State]
+534.7....
+6..195...
+.98....6.
+8...6...3
+4..8.3..1
+7...2...6
+.6....28.
+...419..5
+....8..79
+"#
+        .trim_start();
+        let f = SadManSdk::new();
+        let file = f.read_file_from_string(expected).unwrap();
+        assert_eq!(
+            PuzzleInfo {
+                author: Some("Jane Doe".into()),
+                source: Some("Daily paper".into()),
+                difficulty: Some("Easy".into()),
+                ..Default::default()
+            },
+            file.info
+        );
+        assert_eq!(givens(), file.givens);
+        assert_eq!(
+            vec![(idx(2), Digit::D4)],
+            file.placed.iter_set().collect::<Vec<_>>()
+        );
+        assert_eq!(expected, f.write_file_string(&file));
+
+        // As a grid, the givens alone.
+        let grid: ArrGridRowMajor = read_from_string(&f, expected).unwrap();
+        assert_eq!(givens(), grid);
+        let bare = write_string(&f, &grid);
+        assert!(bare.starts_with("[Puzzle]\n53..7....\n"));
+        assert_eq!(
+            givens(),
+            read_from_string::<_, ArrGridRowMajor>(&f, &bare[9..]).unwrap()
+        );
+
+        // The state contradicts a given.
+        assert_eq!(
+            Err(PuzzleFileReadError::Syntax { line: 15 }),
+            f.read_file_from_string(&expected.replacen("534.7", "634.7", 1))
+        );
+        assert_eq!(
+            Err(PuzzleFileReadError::Syntax { line: 6 }),
+            f.read_file_from_string(&expected.replacen("6..195...", "6..195..", 1))
+        );
+    }
+
+    #[test]
+    fn test_sdx() {
+        let mut file = PuzzleFile {
+            info: PuzzleInfo {
+                description: Some("Candidates".into()),
+                ..Default::default()
+            },
+            givens: givens(),
+            candidates: vec![0; CellIdx::COUNT],
+            ..Default::default()
+        };
+        file.placed[idx(2)] = Some(Digit::D4);
+        file.candidates[3] = 0b10_0010;
+        file.candidates[5] = 0b1000;
+        let f = SadManSdx::new();
+        let s = f.write_file_string(&file);
+        assert!(s.starts_with("#D Candidates\n5 3 u4 26 7 c4 123456789 "));
+        let read = f.read_file_from_string(&s).unwrap();
+        assert_eq!(
+            (&file.info, file.givens, file.placed),
+            (&read.info, read.givens, read.placed)
+        );
+        assert_eq!(
+            [0, 0, 0, 0b10_0010, 0, 0b1000, 0b1_1111_1111],
+            read.candidates[..7]
+        );
+        assert_eq!(s, f.write_file_string(&read));
+        let grid: ArrGridRowMajor = read_from_string(&f, &s).unwrap();
+        assert_eq!(givens(), grid);
+
+        // Every digit is a candidate when none are kept.
+        let s = write_string(&f, &givens());
+        assert!(s.starts_with("5 3 123456789 123456789 7 "));
+        assert_eq!(
+            givens(),
+            read_from_string::<_, ArrGridRowMajor>(&f, &s).unwrap()
+        );
+
+        assert_eq!(
+            Err(PuzzleFileReadError::Syntax { line: 1 }),
+            f.read_file_from_string(&s.replacen("5 3 ", "5 3 u12 ", 1))
+        );
+        assert_eq!(
+            Err(PuzzleFileReadError::Syntax { line: 1 }),
+            f.read_file_from_string(&s.replacen("5 3 ", "5 3 0 ", 1))
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use super::{PuzzleFileReadError, ReadFormatter, WriteFormatter};
+use crate::grid::{CellIdx, Digit, Grid, GridMut};
+use std::io::{Read, Write};
+
+/// Simple Sudoku's .ss files: 9 lines of 9 cells with `.` for empty cells, `|` between boxes and
+/// a line of `-` between bands. The files keep no metadata.
+///
+/// ```text
+/// 53.|.7.|...
+/// 6..|195|...
+/// .98|...|.6.
+/// -----------
+/// ```
+#[derive(Debug, Default)]
+pub struct SimpleSudokuSs;
+
+impl SimpleSudokuSs {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl ReadFormatter for SimpleSudokuSs {
+    type ReadError = PuzzleFileReadError;
+
+    fn read<R, G>(&self, reader: &mut R, grid: &mut G) -> Result<(), Self::ReadError>
+    where
+        R: Read,
+        G: GridMut + ?Sized,
+    {
+        let mut s = String::new();
+        reader
+            .read_to_string(&mut s)
+            .map_err(|_| PuzzleFileReadError::Io)?;
+        let rows = s
+            .lines()
+            .enumerate()
+            .map(|(i, text)| (i + 1, text.trim()))
+            .filter(|(_, text)| !text.is_empty() && !text.chars().all(|c| c == '-' || c == '+'))
+            .collect::<Vec<_>>();
+        if rows.len() != 9 {
+            let line = rows.last().map_or(0, |(line, _)| *line) + 1;
+            return Err(PuzzleFileReadError::Syntax { line });
+        }
+        let cells = CellIdx::iter_row_wise().collect::<Vec<_>>();
+        for ((line, text), cells) in rows.into_iter().zip(cells.chunks(9)) {
+            let syntax = || PuzzleFileReadError::Syntax { line };
+            let text = text.bytes().filter(|c| *c != b'|').collect::<Vec<_>>();
+            if text.len() != 9 {
+                return Err(syntax());
+            }
+            for (c, idx) in text.into_iter().zip(cells) {
+                grid[*idx] = match c {
+                    b'.' | b'0' | b'X' | b'x' => None,
+                    b'1'..=b'9' => Some(Digit::try_from_ascii(c).unwrap()),
+                    _ => return Err(syntax()),
+                };
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WriteFormatter for SimpleSudokuSs {
+    fn write<G, W>(&self, grid: &G, writer: &mut W) -> std::io::Result<usize>
+    where
+        G: Grid + ?Sized,
+        W: Write,
+    {
+        let mut s = String::new();
+        for (idx, value) in grid.iter_row_wise() {
+            let (i, j): (usize, usize) = (idx.row.into(), idx.col.into());
+            s.push(value.map_or('.', |x| x.as_ascii().into()));
+            match j {
+                2 | 5 => s.push('|'),
+                8 if i == 2 || i == 5 => s.push_str("\n-----------\n"),
+                8 => s.push('\n'),
+                _ => (),
+            }
+        }
+        writer.write_all(s.as_bytes())?;
+        Ok(s.len())
+    }
+}
+
+#[cfg(test)]
+mod simple_sudoku_test {
+    use super::SimpleSudokuSs;
+    use crate::format::{read_from_string, write_string, PuzzleFileReadError, RowMajorAscii};
+    use crate::grid::ArrGridRowMajor;
+
+    #[test]
+    fn test_roundtrip() {
+        let expected = r#"
+53.|.7.|...
+6..|195|...
+.98|...|.6.
+-----------
+8..|.6.|..3
+4..|8.3|..1
+7..|.2.|..6
+-----------
+.6.|...|28.
+...|419|..5
+...|.8.|.79
+"#
+        .trim_start();
+        let f = SimpleSudokuSs::new();
+        let grid: ArrGridRowMajor = read_from_string(&f, expected).unwrap();
+        let row_major: ArrGridRowMajor = read_from_string(
+            &RowMajorAscii::default(),
+            "53__7____\n6__195___\n_98____6_\n8___6___3\n4__8_3__1\n7___2___6\n_6____28_\n___419__5\n____8__79",
+        )
+        .unwrap();
+        assert_eq!(row_major, grid);
+        assert_eq!(expected, write_string(&f, &grid));
+        let unboxed = expected.replace(['|', '-'], "").replace('.', "X");
+        assert_eq!(
+            grid,
+            read_from_string::<_, ArrGridRowMajor>(&f, &unboxed).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let f = SimpleSudokuSs::new();
+        let rows = ["...|...|..."; 9].join("\n");
+        let read = |s: &str| read_from_string::<_, ArrGridRowMajor>(&f, s).map(|_| ());
+        assert_eq!(Ok(()), read(&rows));
+        assert_eq!(
+            Err(PuzzleFileReadError::Syntax { line: 1 }),
+            read(&rows.replacen("...|", "..|", 1))
+        );
+        assert_eq!(
+            Err(PuzzleFileReadError::Syntax { line: 2 }),
+            read(&rows.replacen("\n...", "\n..a", 1))
+        );
+        assert_eq!(
+            Err(PuzzleFileReadError::Syntax { line: 9 }),
+            read(&rows[..rows.len() - 12])
+        );
+    }
+}
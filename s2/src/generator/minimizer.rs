@@ -0,0 +1,193 @@
+use super::{GeneratorError, Symmetry};
+use crate::cancellation_flag::CancellationFlag;
+use crate::grid::{ArrGridRowMajor, CellIdx, Grid, GridMut, GridMutWithDefault};
+use crate::solver::CountingSolver;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Removes the givens of each orbit in turn. A removal is kept if `allowed` accepts the resulting
+/// clue count, the solution stays unique and `keep` accepts the resulting puzzle. Returns the
+/// number of givens left.
+pub(super) fn remove_orbits<C, P, F>(
+    cancellation_flag: &C,
+    solver: &CountingSolver,
+    puzzle: &mut ArrGridRowMajor,
+    orbits: &[Vec<CellIdx>],
+    allowed: P,
+    mut keep: F,
+) -> Result<usize, GeneratorError>
+where
+    C: CancellationFlag,
+    P: Fn(usize) -> bool,
+    F: FnMut(&ArrGridRowMajor) -> Result<bool, GeneratorError>,
+{
+    let mut clues = puzzle.iter_set().count();
+    for orbit in orbits {
+        let removed = orbit
+            .iter()
+            .filter_map(|idx| Some((*idx, puzzle[*idx]?)))
+            .collect::<Vec<_>>();
+        if removed.is_empty() || !allowed(clues - removed.len()) {
+            continue;
+        }
+        puzzle.unset_from_iter(removed.iter().map(|(idx, _)| *idx));
+        if solver.is_unique(cancellation_flag, puzzle)? && keep(puzzle)? {
+            clues -= removed.len();
+        } else {
+            puzzle.set_from_iter(removed.into_iter());
+        }
+    }
+    Ok(clues)
+}
+
+/// Removes givens of `grid` in `order` while the solution stays unique, and returns a minimal
+/// puzzle: one where no given can be removed. With a `symmetry`, the givens of a cell's orbit are
+/// removed together, and the result is minimal with respect to removing whole orbits.
+pub fn minimize_in_order<C, T, I>(
+    cancellation_flag: &C,
+    grid: &T,
+    order: I,
+    symmetry: Option<Symmetry>,
+) -> Result<ArrGridRowMajor, GeneratorError>
+where
+    C: CancellationFlag,
+    T: Grid + ?Sized,
+    I: IntoIterator<Item = CellIdx>,
+{
+    if !CountingSolver::new().is_unique(cancellation_flag, grid)? {
+        return Err(GeneratorError::NotUnique);
+    }
+    let symmetry = symmetry.unwrap_or_default();
+    let orbits = order
+        .into_iter()
+        .map(|idx| symmetry.orbit(idx))
+        .collect::<Vec<_>>();
+    let mut puzzle = ArrGridRowMajor::copy_of(grid);
+    // A removal that fails once fails for good, as removing more givens only adds solutions, so a
+    // single pass leaves a minimal puzzle.
+    remove_orbits(
+        cancellation_flag,
+        &CountingSolver::new(),
+        &mut puzzle,
+        &orbits,
+        |_| true,
+        |_| Ok(true),
+    )?;
+    Ok(puzzle)
+}
+
+/// Same as `minimize_in_order`, trying the givens in random order.
+pub fn minimize<C, T, R>(
+    cancellation_flag: &C,
+    grid: &T,
+    rng: &mut R,
+    symmetry: Option<Symmetry>,
+) -> Result<ArrGridRowMajor, GeneratorError>
+where
+    C: CancellationFlag,
+    T: Grid + ?Sized,
+    R: Rng + ?Sized,
+{
+    let mut order = grid.iter_set().map(|(idx, _)| idx).collect::<Vec<_>>();
+    order.shuffle(rng);
+    minimize_in_order(cancellation_flag, grid, order, symmetry)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{minimize, minimize_in_order};
+    use crate::cancellation_flag::NeverCancelled;
+    use crate::format::{read_from_string, RowMajorAscii};
+    use crate::generator::{random_solution_grid, GeneratorError, Symmetry};
+    use crate::grid::{ArrGridRowMajor, CellIdx, Grid, GridMut};
+    use crate::solver::CountingSolver;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn given() -> ArrGridRowMajor {
+        let given = r#"
+53__7____
+6__195___
+_98____6_
+8___6___3
+4__8_3__1
+7___2___6
+_6____28_
+___419__5
+____8__79
+"#
+        .trim();
+        read_from_string(&RowMajorAscii::default(), given).unwrap()
+    }
+
+    fn assert_minimal(original: &ArrGridRowMajor, puzzle: &ArrGridRowMajor, symmetry: Symmetry) {
+        let solver = CountingSolver::new();
+        assert_eq!(Ok(true), solver.is_unique(&NeverCancelled::new(), puzzle));
+        assert!(puzzle
+            .iter_set()
+            .all(|(idx, value)| original[idx] == Some(value)));
+        puzzle.iter_set().for_each(|(idx, _)| {
+            let mut reduced = *puzzle;
+            reduced.unset_from_iter(symmetry.orbit(idx).into_iter());
+            assert_eq!(
+                Ok(false),
+                solver.is_unique(&NeverCancelled::new(), &reduced),
+                "{idx:?}"
+            );
+        });
+    }
+
+    #[test]
+    fn test_minimal() {
+        let given = given();
+        let mut rng = StdRng::seed_from_u64(0);
+        let puzzle = minimize(&NeverCancelled::new(), &given, &mut rng, None).unwrap();
+        assert!(puzzle.iter_set().count() < given.iter_set().count());
+        assert_minimal(&given, &puzzle, Symmetry::None);
+    }
+
+    #[test]
+    fn test_in_order() {
+        let given = given();
+        let minimize = || {
+            minimize_in_order(
+                &NeverCancelled::new(),
+                &given,
+                CellIdx::iter_row_wise(),
+                None,
+            )
+            .unwrap()
+        };
+        assert_eq!(minimize(), minimize());
+        assert_minimal(&given, &minimize(), Symmetry::None);
+    }
+
+    #[test]
+    fn test_symmetry() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let solution = random_solution_grid(&mut rng);
+        let puzzle = minimize(
+            &NeverCancelled::new(),
+            &solution,
+            &mut rng,
+            Some(Symmetry::Rotational),
+        )
+        .unwrap();
+        assert!(Symmetry::Rotational.holds(&puzzle));
+        assert_minimal(&solution, &puzzle, Symmetry::Rotational);
+    }
+
+    #[test]
+    fn test_not_unique() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            Err(GeneratorError::NotUnique),
+            minimize(
+                &NeverCancelled::new(),
+                &ArrGridRowMajor::new(),
+                &mut rng,
+                None
+            )
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use crate::cancellation_flag::CancellationFlag;
+use crate::constraint::Constraint;
+use crate::grid::{ArrGridRowMajor, CellIdx};
+use crate::rater::{Rater, Technique};
+use crate::solver::{CountingSolver, SolverError};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::ops::RangeInclusive;
+use thiserror::Error;
+
+mod minimizer;
+use minimizer::remove_orbits;
+pub use minimizer::{minimize, minimize_in_order};
+
+mod solution_grid;
+pub use solution_grid::{random_solution_grid, random_solution_grid_with};
+
+mod symmetry;
+pub use symmetry::Symmetry;
+
+/// No 9x9 sudoku with fewer givens has a unique solution.
+pub const MIN_CLUES: usize = 17;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clues {
+    /// Stop removing givens once exactly this many are left.
+    Target(usize),
+    /// Remove givens while the solution stays unique, accept the puzzle if at most this many are
+    /// left.
+    AtMost(usize),
+}
+
+impl Default for Clues {
+    fn default() -> Self {
+        Self::AtMost(CellIdx::COUNT)
+    }
+}
+
+#[derive(Debug, Default, Error, Eq, PartialEq)]
+#[error("Puzzle generation failed")]
+pub enum GeneratorError {
+    #[error("no puzzle with requested clues and symmetry")]
+    #[default]
+    Infeasible,
+    #[error("cancelled")]
+    Cancelled,
+    #[error("puzzle does not have a unique solution")]
+    NotUnique,
+}
+
+impl From<SolverError> for GeneratorError {
+    fn from(err: SolverError) -> Self {
+        match err {
+            SolverError::Cancelled => Self::Cancelled,
+            SolverError::Infeasible | SolverError::ConstraintsViolated => Self::Infeasible,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Generator {
+    clues: Clues,
+    symmetry: Symmetry,
+    difficulty: RangeInclusive<Technique>,
+    solver: CountingSolver,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new(None, None, None)
+    }
+}
+
+impl Generator {
+    /// `difficulty` bounds the hardest technique `Rater` needs to solve the puzzle.
+    pub fn new(
+        clues: Option<Clues>,
+        symmetry: Option<Symmetry>,
+        difficulty: Option<RangeInclusive<Technique>>,
+    ) -> Self {
+        Self {
+            clues: clues.unwrap_or_default(),
+            symmetry: symmetry.unwrap_or_default(),
+            difficulty: difficulty.unwrap_or(Technique::HiddenSingle..=Technique::Guess),
+            solver: CountingSolver::new(),
+        }
+    }
+
+    /// Generates puzzles whose solution satisfies `constraints` and is unique given them. The
+    /// difficulty is still rated on rows, columns and boxes alone.
+    pub fn with_constraints(mut self, constraints: Vec<Box<dyn Constraint>>) -> Self {
+        self.solver = CountingSolver::new().with_constraints(constraints);
+        self
+    }
+
+    fn feasible(&self) -> bool {
+        // Givens are removed an orbit at a time, so only sums of orbit sizes can be left.
+        let mut reachable = [false; CellIdx::COUNT + 1];
+        reachable[0] = true;
+        for orbit in self.symmetry.orbits() {
+            for cnt in (orbit.len()..=CellIdx::COUNT).rev() {
+                reachable[cnt] |= reachable[cnt - orbit.len()];
+            }
+        }
+        if self.difficulty.is_empty() {
+            return false;
+        }
+        match self.clues {
+            Clues::Target(target) => {
+                (MIN_CLUES..=CellIdx::COUNT).contains(&target) && reachable[target]
+            }
+            Clues::AtMost(at_most) => {
+                at_most >= MIN_CLUES
+                    && reachable[MIN_CLUES..=at_most.min(CellIdx::COUNT)]
+                        .iter()
+                        .any(|x| *x)
+            }
+        }
+    }
+
+    /// Generates a puzzle with a unique solution. Givens are removed from a random complete grid
+    /// one symmetry orbit at a time, and every removal is kept only if the solution stays unique
+    /// and the puzzle does not get harder than the difficulty range allows. Retries with a fresh
+    /// grid until both the clue count and the difficulty are met or `cancellation_flag` is raised.
+    pub fn generate<C, R>(
+        &self,
+        cancellation_flag: &C,
+        rng: &mut R,
+    ) -> Result<ArrGridRowMajor, GeneratorError>
+    where
+        C: CancellationFlag,
+        R: Rng + ?Sized,
+    {
+        if !self.feasible() {
+            return Err(GeneratorError::Infeasible);
+        }
+        loop {
+            if cancellation_flag.cancelled() {
+                return Err(GeneratorError::Cancelled);
+            }
+            let rules = self.solver.rules();
+            let solution = match rules.variants().constraints.is_empty() {
+                true => random_solution_grid(rng),
+                false => random_solution_grid_with(rng, rules).ok_or(GeneratorError::Infeasible)?,
+            };
+            if let Some(puzzle) = self.reduce(cancellation_flag, rng, &solution)? {
+                return Ok(puzzle);
+            }
+        }
+    }
+
+    fn reduce<C, R>(
+        &self,
+        cancellation_flag: &C,
+        rng: &mut R,
+        solution: &ArrGridRowMajor,
+    ) -> Result<Option<ArrGridRowMajor>, GeneratorError>
+    where
+        C: CancellationFlag,
+        R: Rng + ?Sized,
+    {
+        let rater = Rater::new();
+        let hardest = |puzzle: &ArrGridRowMajor| -> Result<Technique, GeneratorError> {
+            Ok(rater.rate(puzzle)?.unwrap_or(Technique::HiddenSingle))
+        };
+        let mut puzzle = *solution;
+        let mut orbits = self.symmetry.orbits();
+        orbits.shuffle(rng);
+        let clues = remove_orbits(
+            cancellation_flag,
+            &self.solver,
+            &mut puzzle,
+            &orbits,
+            |clues| match self.clues {
+                Clues::Target(target) => clues >= target,
+                Clues::AtMost(_) => true,
+            },
+            // Removing givens never makes a puzzle easier, so a removal that overshoots the
+            // difficulty range is reverted right away.
+            |puzzle| {
+                Ok(*self.difficulty.end() == Technique::Guess
+                    || hardest(puzzle)? <= *self.difficulty.end())
+            },
+        )?;
+        let accepted = match self.clues {
+            Clues::Target(target) => clues == target,
+            Clues::AtMost(at_most) => clues <= at_most,
+        } && self.difficulty.contains(&hardest(&puzzle)?);
+        Ok(accepted.then_some(puzzle))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{random_solution_grid_with, Clues, Generator, GeneratorError, Symmetry};
+    use crate::cancellation_flag::NeverCancelled;
+    use crate::constraint::{Constraint, Distinct, Rules, Variants};
+    use crate::grid::{CellIdx, ColIdx, Grid, RowIdx};
+    use crate::rater::{Rater, Technique};
+    use crate::solver::CountingSolver;
+    use crate::status::{eval_status, eval_variants_status, SudokuStatus};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_target() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let puzzle = Generator::new(Some(Clues::Target(30)), None, None)
+            .generate(&NeverCancelled::new(), &mut rng)
+            .unwrap();
+        assert_eq!(30, puzzle.iter_set().count());
+        assert!(eval_status(&puzzle).is_ok());
+        assert_eq!(
+            Ok(true),
+            CountingSolver::new().is_unique(&NeverCancelled::new(), &puzzle)
+        );
+    }
+
+    #[test]
+    fn test_symmetry() {
+        let mut rng = StdRng::seed_from_u64(0);
+        [
+            Symmetry::Rotational,
+            Symmetry::Diagonal,
+            Symmetry::Mirror,
+            Symmetry::Dihedral,
+        ]
+        .into_iter()
+        .for_each(|symmetry| {
+            let puzzle = Generator::new(Some(Clues::AtMost(40)), Some(symmetry), None)
+                .generate(&NeverCancelled::new(), &mut rng)
+                .unwrap();
+            assert!(puzzle.iter_set().count() <= 40);
+            assert!(symmetry.holds(&puzzle), "{symmetry:?}\n{puzzle:?}");
+            assert_eq!(
+                Ok(true),
+                CountingSolver::new().is_unique(&NeverCancelled::new(), &puzzle)
+            );
+        })
+    }
+
+    #[test]
+    fn test_infeasible() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            Err(GeneratorError::Infeasible),
+            Generator::new(Some(Clues::AtMost(16)), None, None)
+                .generate(&NeverCancelled::new(), &mut rng)
+        );
+        // Dihedral orbits have 1, 4 or 8 cells, so the clue count is 0 or 1 modulo 4.
+        assert_eq!(
+            Err(GeneratorError::Infeasible),
+            Generator::new(Some(Clues::Target(30)), Some(Symmetry::Dihedral), None)
+                .generate(&NeverCancelled::new(), &mut rng)
+        );
+    }
+
+    #[test]
+    fn test_difficulty() {
+        let mut rng = StdRng::seed_from_u64(0);
+        [
+            Technique::HiddenSingle..=Technique::HiddenSingle,
+            Technique::NakedSingle..=Technique::LockedCandidates,
+            Technique::NakedPair..=Technique::Jellyfish,
+        ]
+        .into_iter()
+        .for_each(|difficulty| {
+            let puzzle = Generator::new(None, None, Some(difficulty.clone()))
+                .generate(&NeverCancelled::new(), &mut rng)
+                .unwrap();
+            let rating = Rater::new().rate(&puzzle).unwrap().unwrap();
+            assert!(difficulty.contains(&rating), "{rating}\n{puzzle:?}");
+            assert_eq!(
+                Ok(true),
+                CountingSolver::new().is_unique(&NeverCancelled::new(), &puzzle)
+            );
+        })
+    }
+
+    #[test]
+    fn test_empty_difficulty() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            Err(GeneratorError::Infeasible),
+            Generator::new(None, None, Some(Technique::Guess..=Technique::XWing))
+                .generate(&NeverCancelled::new(), &mut rng)
+        );
+    }
+
+    #[test]
+    fn test_constraints() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let diagonal = || -> Vec<Box<dyn Constraint>> {
+            let cells = (0..9)
+                .map(|i| (RowIdx::try_from(i).unwrap(), ColIdx::try_from(i).unwrap()).into())
+                .collect::<Vec<CellIdx>>();
+            vec![Box::new(Distinct::new(cells))]
+        };
+        let puzzle = Generator::new(Some(Clues::AtMost(40)), None, None)
+            .with_constraints(diagonal())
+            .generate(&NeverCancelled::new(), &mut rng)
+            .unwrap();
+        assert!(eval_status(&puzzle).is_ok());
+        let solver = CountingSolver::new().with_constraints(diagonal());
+        assert_eq!(Ok(true), solver.is_unique(&NeverCancelled::new(), &puzzle));
+        let rules = Rules::from(Variants {
+            constraints: diagonal(),
+            ..Default::default()
+        });
+        let solution = random_solution_grid_with(&mut rng, &rules).unwrap();
+        assert_eq!(
+            Ok(SudokuStatus::Complete),
+            eval_variants_status(&solution, &rules).map_err(|_| ())
+        );
+    }
+}
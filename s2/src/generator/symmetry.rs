@@ -0,0 +1,104 @@
+use crate::grid::{CellIdx, ColIdx, Grid, RowIdx, DIM};
+use itertools::Itertools;
+use strum_macros::EnumIter as EnumIterMacro;
+
+const LAST: usize = DIM - 1;
+
+/// Symmetry of the pattern of givens. Cells mapped onto each other are given or empty together.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIterMacro)]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// 180° rotation around the center cell.
+    Rotational,
+    /// Reflection in the main diagonal.
+    Diagonal,
+    /// Reflection in the vertical axis.
+    Mirror,
+    /// All rotations and reflections of the square.
+    Dihedral,
+}
+
+impl Symmetry {
+    fn images(&self, (i, j): (usize, usize)) -> Vec<(usize, usize)> {
+        match self {
+            Self::None => vec![(i, j)],
+            Self::Rotational => vec![(i, j), (LAST - i, LAST - j)],
+            Self::Diagonal => vec![(i, j), (j, i)],
+            Self::Mirror => vec![(i, j), (i, LAST - j)],
+            Self::Dihedral => vec![
+                (i, j),
+                (j, LAST - i),
+                (LAST - i, LAST - j),
+                (LAST - j, i),
+                (i, LAST - j),
+                (LAST - i, j),
+                (j, i),
+                (LAST - j, LAST - i),
+            ],
+        }
+    }
+
+    /// Cells `idx` is mapped onto, including `idx` itself, in row-major order.
+    pub fn orbit(&self, idx: CellIdx) -> Vec<CellIdx> {
+        self.images((idx.row.into(), idx.col.into()))
+            .into_iter()
+            .map(|(i, j)| {
+                CellIdx::from((RowIdx::try_from(i).unwrap(), ColIdx::try_from(j).unwrap()))
+            })
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    /// Partition of all cells into orbits.
+    pub fn orbits(&self) -> Vec<Vec<CellIdx>> {
+        CellIdx::iter_row_wise()
+            .filter_map(|idx| {
+                let orbit = self.orbit(idx);
+                (orbit[0] == idx).then_some(orbit)
+            })
+            .collect()
+    }
+
+    /// Whether the set cells of `grid` follow the symmetry.
+    pub fn holds<T>(&self, grid: &T) -> bool
+    where
+        T: Grid + ?Sized,
+    {
+        self.orbits()
+            .iter()
+            .all(|orbit| orbit.iter().map(|idx| grid[*idx].is_some()).all_equal())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Symmetry;
+    use crate::grid::CellIdx;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_orbits_partition_cells() {
+        Symmetry::iter().for_each(|symmetry| {
+            let mut cells = symmetry.orbits().into_iter().flatten().collect::<Vec<_>>();
+            cells.sort();
+            assert_eq!(CellIdx::iter_row_wise().collect::<Vec<_>>(), cells);
+        })
+    }
+
+    #[test]
+    fn test_orbit_sizes() {
+        let sizes = |symmetry: Symmetry| {
+            let mut sizes = symmetry.orbits().iter().map(Vec::len).collect::<Vec<_>>();
+            sizes.sort();
+            sizes.dedup();
+            sizes
+        };
+        assert_eq!(vec![1], sizes(Symmetry::None));
+        assert_eq!(vec![1, 2], sizes(Symmetry::Rotational));
+        assert_eq!(vec![1, 2], sizes(Symmetry::Diagonal));
+        assert_eq!(vec![1, 2], sizes(Symmetry::Mirror));
+        assert_eq!(vec![1, 4, 8], sizes(Symmetry::Dihedral));
+    }
+}
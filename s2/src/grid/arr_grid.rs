@@ -1,9 +1,13 @@
-use super::{CellIdx, Digit};
+use super::{BoxShape, CellIdx, Digit, Shape, Shape9x9, SizedCellIdx};
 use std::cmp::Ordering;
 use std::ops::{Index, IndexMut};
 
-#[derive(Clone, Copy)]
-pub struct ArrGrid<const ROW_MAJOR: bool>([Option<Digit>; CellIdx::COUNT]);
+/// Cells of a grid of shape `S`, stored row by row or column by column. Classic 9x9 grids are
+/// `Grid`s, indexed by `CellIdx`; grids of any shape are indexed by `SizedCellIdx`.
+#[derive(Clone)]
+pub struct ArrGrid<const ROW_MAJOR: bool, S: Shape = Shape9x9>(S::Cells);
+
+impl<const ROW_MAJOR: bool> Copy for ArrGrid<ROW_MAJOR> {}
 
 impl<const ROW_MAJOR: bool> ArrGrid<ROW_MAJOR> {
     pub fn new() -> Self {
@@ -19,9 +23,37 @@ impl<const ROW_MAJOR: bool> ArrGrid<ROW_MAJOR> {
     }
 }
 
-impl<const ROW_MAJOR: bool> Default for ArrGrid<ROW_MAJOR> {
+impl<const ROW_MAJOR: bool, S: Shape> ArrGrid<ROW_MAJOR, S> {
+    fn sized_idx(idx: SizedCellIdx) -> usize {
+        if ROW_MAJOR {
+            idx.row_major::<S>()
+        } else {
+            idx.col_major::<S>()
+        }
+    }
+}
+
+impl<const ROW_MAJOR: bool, const WIDTH: usize, const HEIGHT: usize>
+    ArrGrid<ROW_MAJOR, BoxShape<WIDTH, HEIGHT>>
+{
+    pub fn iter_row_wise(&self) -> impl Iterator<Item = (SizedCellIdx, Option<u8>)> + '_ {
+        BoxShape::<WIDTH, HEIGHT>::iter_row_wise().map(|idx| (idx, self[idx]))
+    }
+
+    pub fn iter_set(&self) -> impl Iterator<Item = (SizedCellIdx, u8)> + '_ {
+        self.iter_row_wise()
+            .filter_map(|(idx, value)| Some((idx, value?)))
+    }
+
+    pub fn iter_unset(&self) -> impl Iterator<Item = SizedCellIdx> + '_ {
+        self.iter_row_wise()
+            .filter_map(|(idx, value)| value.is_none().then_some(idx))
+    }
+}
+
+impl<const ROW_MAJOR: bool, S: Shape> Default for ArrGrid<ROW_MAJOR, S> {
     fn default() -> Self {
-        Self([None; CellIdx::COUNT])
+        Self(S::empty_cells())
     }
 }
 
@@ -39,16 +71,30 @@ impl<const ROW_MAJOR: bool> IndexMut<CellIdx> for ArrGrid<ROW_MAJOR> {
     }
 }
 
+impl<const ROW_MAJOR: bool, S: Shape> Index<SizedCellIdx> for ArrGrid<ROW_MAJOR, S> {
+    type Output = Option<S::Digit>;
+
+    fn index(&self, idx: SizedCellIdx) -> &Self::Output {
+        &self.0.as_ref()[Self::sized_idx(idx)]
+    }
+}
+
+impl<const ROW_MAJOR: bool, S: Shape> IndexMut<SizedCellIdx> for ArrGrid<ROW_MAJOR, S> {
+    fn index_mut(&mut self, idx: SizedCellIdx) -> &mut Self::Output {
+        &mut self.0.as_mut()[Self::sized_idx(idx)]
+    }
+}
+
 impl super::Grid for ArrGrid<true> {
     fn iter_row_wise(&self) -> impl Iterator<Item = (CellIdx, Option<Digit>)> {
         self.0
             .iter()
             .enumerate()
-            .map(|(idx, value)| (CellIdx::try_of_row_major(idx).unwrap(), value.clone()))
+            .map(|(idx, value)| (CellIdx::try_of_row_major(idx).unwrap(), *value))
     }
 
     fn iter_col_wise(&self) -> impl Iterator<Item = (CellIdx, Option<Digit>)> {
-        CellIdx::iter_col_wise().map(|idx| (idx, self[idx].clone()))
+        CellIdx::iter_col_wise().map(|idx| (idx, self[idx]))
     }
 
     fn iter(&self) -> impl Iterator<Item = (CellIdx, Option<Digit>)> {
@@ -58,7 +104,7 @@ impl super::Grid for ArrGrid<true> {
 
 impl super::Grid for ArrGrid<false> {
     fn iter_row_wise(&self) -> impl Iterator<Item = (CellIdx, Option<Digit>)> {
-        CellIdx::iter_row_wise().map(|idx| (idx, self[idx].clone()))
+        CellIdx::iter_row_wise().map(|idx| (idx, self[idx]))
     }
 
     fn iter_col_wise(&self) -> impl Iterator<Item = (CellIdx, Option<Digit>)> {
@@ -111,3 +157,30 @@ where
         super::fmt(self, f)
     }
 }
+
+impl<const ROW_MAJOR: bool, const WIDTH: usize, const HEIGHT: usize> PartialEq
+    for ArrGrid<ROW_MAJOR, BoxShape<WIDTH, HEIGHT>>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<const ROW_MAJOR: bool, const WIDTH: usize, const HEIGHT: usize> Eq
+    for ArrGrid<ROW_MAJOR, BoxShape<WIDTH, HEIGHT>>
+{
+}
+
+impl<const ROW_MAJOR: bool, const WIDTH: usize, const HEIGHT: usize> std::fmt::Debug
+    for ArrGrid<ROW_MAJOR, BoxShape<WIDTH, HEIGHT>>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut rows = super::ArrGridRowMajor::<BoxShape<WIDTH, HEIGHT>>::default();
+        self.iter_row_wise()
+            .for_each(|(idx, value)| rows[idx] = value);
+        let s = crate::format::RowMajorAscii::default()
+            .write_sized_string(&rows)
+            .map_err(|_| std::fmt::Error)?;
+        f.write_str(&s)
+    }
+}
@@ -0,0 +1,148 @@
+use super::{CellIdx, ColIdx, RowIdx, DIM};
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum BoxLayoutError {
+    #[error("box index must be less than 9")]
+    OutOfRange,
+    #[error("box must have exactly 9 cells")]
+    Size,
+    #[error("box cells must be connected")]
+    Disconnected,
+}
+
+/// Partition of the grid into the nine boxes that must hold every digit once. The classic layout
+/// has 3x3 boxes, jigsaw sudoku uses irregular ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoxLayout {
+    /// Box of each cell in row-major order.
+    boxes: [u8; CellIdx::COUNT],
+}
+
+impl Default for BoxLayout {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+impl BoxLayout {
+    /// `boxes` holds the box of each cell in row-major order. Each box must be made of 9 cells
+    /// connected through their sides.
+    pub fn new(boxes: [u8; CellIdx::COUNT]) -> Result<Self, BoxLayoutError> {
+        if boxes.iter().any(|box_| usize::from(*box_) >= DIM) {
+            return Err(BoxLayoutError::OutOfRange);
+        }
+        let t = Self { boxes };
+        for box_ in 0..DIM {
+            if t.cells(box_).count() != DIM {
+                return Err(BoxLayoutError::Size);
+            }
+            if !t.is_connected(box_) {
+                return Err(BoxLayoutError::Disconnected);
+            }
+        }
+        Ok(t)
+    }
+
+    pub fn classic() -> Self {
+        let mut boxes = [0; CellIdx::COUNT];
+        CellIdx::iter_row_wise().for_each(|idx| boxes[idx.row_major()] = idx.box_() as u8);
+        Self { boxes }
+    }
+
+    pub fn box_of(&self, idx: CellIdx) -> usize {
+        self.boxes[idx.row_major()].into()
+    }
+
+    pub fn cells(&self, box_: usize) -> impl Iterator<Item = CellIdx> + '_ {
+        CellIdx::iter_row_wise().filter(move |idx| self.box_of(*idx) == box_)
+    }
+
+    fn is_connected(&self, box_: usize) -> bool {
+        let mut seen = [false; CellIdx::COUNT];
+        let mut stack = self.cells(box_).take(1).collect::<Vec<_>>();
+        let mut count = 0;
+        while let Some(idx) = stack.pop() {
+            if seen[idx.row_major()] {
+                continue;
+            }
+            seen[idx.row_major()] = true;
+            count += 1;
+            let (i, j): (usize, usize) = (idx.row.into(), idx.col.into());
+            [
+                (i.wrapping_sub(1), j),
+                (i + 1, j),
+                (i, j.wrapping_sub(1)),
+                (i, j + 1),
+            ]
+            .into_iter()
+            .filter_map(|(i, j)| Some((RowIdx::try_from(i).ok()?, ColIdx::try_from(j).ok()?)))
+            .map(CellIdx::from)
+            .filter(|idx| self.box_of(*idx) == box_ && !seen[idx.row_major()])
+            .for_each(|idx| stack.push(idx));
+        }
+        count == DIM
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BoxLayout, BoxLayoutError};
+    use crate::grid::CellIdx;
+
+    fn boxes(s: &str) -> [u8; CellIdx::COUNT] {
+        let boxes = s
+            .bytes()
+            .filter(|c| !c.is_ascii_whitespace())
+            .map(|c| c - b'A')
+            .collect::<Vec<_>>();
+        boxes.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let jigsaw = r#"
+AAABBBCCC
+AAAABBCCC
+AADBBBCCC
+DDDEBEFFF
+DDEEEEFFF
+DDDEEEFFF
+GGGHHHIII
+GGGHHHIII
+GGGHHHIII
+"#;
+        let layout = BoxLayout::new(boxes(jigsaw)).unwrap();
+        assert_eq!(3, layout.box_of(CellIdx::try_of_row_major(20).unwrap()));
+        assert_eq!(
+            vec![0, 1, 2, 9, 10, 11, 12, 18, 19],
+            layout
+                .cells(0)
+                .map(|idx| idx.row_major())
+                .collect::<Vec<_>>()
+        );
+
+        let classic = "AAABBBCCC".repeat(3) + &"DDDEEEFFF".repeat(3) + &"GGGHHHIII".repeat(3);
+        assert_eq!(
+            BoxLayout::classic(),
+            BoxLayout::new(boxes(&classic)).unwrap()
+        );
+
+        let mut out_of_range = boxes(jigsaw);
+        out_of_range[0] = 9;
+        assert_eq!(
+            Err(BoxLayoutError::OutOfRange),
+            BoxLayout::new(out_of_range)
+        );
+        let mut size = boxes(jigsaw);
+        size[0] = 1;
+        assert_eq!(Err(BoxLayoutError::Size), BoxLayout::new(size));
+        // Swapping two cells keeps the sizes, but cuts them off their boxes.
+        let mut disconnected = boxes(jigsaw);
+        disconnected.swap(0, 80);
+        assert_eq!(
+            Err(BoxLayoutError::Disconnected),
+            BoxLayout::new(disconnected)
+        );
+    }
+}
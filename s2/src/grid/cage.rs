@@ -0,0 +1,120 @@
+use super::{CellIdx, Digit};
+use itertools::Itertools;
+use strum::{EnumCount, IntoEnumIterator};
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum CageError {
+    #[error("cage has no cells")]
+    Empty,
+    #[error("cage has a repeated cell")]
+    RepeatedCell,
+    #[error("no distinct digits add up to the cage sum")]
+    Infeasible,
+}
+
+/// Killer sudoku cage: cells holding distinct digits that add up to `sum`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cage {
+    cells: Vec<CellIdx>,
+    sum: u8,
+}
+
+impl Cage {
+    pub fn new(cells: Vec<CellIdx>, sum: u8) -> Result<Self, CageError> {
+        if cells.is_empty() {
+            return Err(CageError::Empty);
+        }
+        if !cells.iter().all_unique() {
+            return Err(CageError::RepeatedCell);
+        }
+        if sum_combination_masks(cells.len(), sum).next().is_none() {
+            return Err(CageError::Infeasible);
+        }
+        Ok(Self { cells, sum })
+    }
+
+    pub fn cells(&self) -> &[CellIdx] {
+        &self.cells
+    }
+
+    pub fn sum(&self) -> u8 {
+        self.sum
+    }
+
+    pub fn combinations(&self) -> Vec<Vec<Digit>> {
+        sum_combinations(self.cells.len(), self.sum)
+    }
+
+    pub(crate) fn combination_masks(&self) -> impl Iterator<Item = u16> {
+        sum_combination_masks(self.cells.len(), self.sum)
+    }
+}
+
+/// Sets of `size` distinct digits adding up to `sum`, one bit per digit.
+fn sum_combination_masks(size: usize, sum: u8) -> impl Iterator<Item = u16> {
+    (0u16..1 << Digit::COUNT).filter(move |mask| {
+        mask.count_ones() as usize == size
+            && Digit::iter()
+                .filter(|x| mask & (1 << u8::from(x)) != 0)
+                .map(|x| u16::from(u8::from(x) + 1))
+                .sum::<u16>()
+                == u16::from(sum)
+    })
+}
+
+/// Every set of `size` distinct digits adding up to `sum`, each in increasing order, in
+/// lexicographic order.
+pub fn sum_combinations(size: usize, sum: u8) -> Vec<Vec<Digit>> {
+    let mut combinations = sum_combination_masks(size, sum)
+        .map(|mask| {
+            Digit::iter()
+                .filter(|x| mask & (1 << u8::from(x)) != 0)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    combinations.sort();
+    combinations
+}
+
+#[cfg(test)]
+mod test {
+    use super::{sum_combinations, Cage, CageError};
+    use crate::grid::{CellIdx, Digit};
+
+    fn digits(s: &str) -> Vec<Digit> {
+        s.bytes()
+            .map(|x| Digit::try_from_ascii(x).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_sum_combinations() {
+        assert_eq!(vec![digits("12")], sum_combinations(2, 3));
+        assert_eq!(
+            vec![digits("1236"), digits("1245")],
+            sum_combinations(4, 12)
+        );
+        assert_eq!(vec![digits("5")], sum_combinations(1, 5));
+        assert_eq!(vec![digits("123456789")], sum_combinations(9, 45));
+        assert!(sum_combinations(2, 2).is_empty());
+        assert!(sum_combinations(2, 18).is_empty());
+        assert_eq!(8, sum_combinations(3, 15).len());
+    }
+
+    #[test]
+    fn test_new() {
+        let idx = |x| CellIdx::try_of_row_major(x).unwrap();
+        assert_eq!(Err(CageError::Empty), Cage::new(vec![], 0));
+        assert_eq!(
+            Err(CageError::RepeatedCell),
+            Cage::new(vec![idx(0), idx(0)], 3)
+        );
+        assert_eq!(
+            Err(CageError::Infeasible),
+            Cage::new(vec![idx(0), idx(1)], 1)
+        );
+        let cage = Cage::new(vec![idx(0), idx(1)], 17).unwrap();
+        assert_eq!(vec![digits("89")], cage.combinations());
+    }
+}
@@ -0,0 +1,73 @@
+use super::{CellIdx, ColIdx, RowIdx};
+
+/// Chess piece move no two equal digits may be apart, as in anti-knight and anti-king sudoku.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChessMove {
+    Knight,
+    King,
+}
+
+impl ChessMove {
+    fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Self::Knight => &[
+                (-2, -1),
+                (-2, 1),
+                (-1, -2),
+                (-1, 2),
+                (1, -2),
+                (1, 2),
+                (2, -1),
+                (2, 1),
+            ],
+            Self::King => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+        }
+    }
+
+    /// Cells one move away from `idx`.
+    pub fn peers(self, idx: CellIdx) -> impl Iterator<Item = CellIdx> {
+        let (i, j): (usize, usize) = (idx.row.into(), idx.col.into());
+        self.offsets().iter().filter_map(move |(di, dj)| {
+            let row = RowIdx::try_from(i.checked_add_signed(*di)?).ok()?;
+            let col = ColIdx::try_from(j.checked_add_signed(*dj)?).ok()?;
+            Some((row, col).into())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ChessMove;
+    use crate::grid::CellIdx;
+
+    #[test]
+    fn test_peers() {
+        let peers = |m: ChessMove, idx| {
+            let mut peers = m
+                .peers(CellIdx::try_of_row_major(idx).unwrap())
+                .map(|idx| idx.row_major())
+                .collect::<Vec<_>>();
+            peers.sort();
+            peers
+        };
+        assert_eq!(vec![11, 19], peers(ChessMove::Knight, 0));
+        assert_eq!(vec![1, 9, 10], peers(ChessMove::King, 0));
+        assert_eq!(
+            vec![21, 23, 29, 33, 47, 51, 57, 59],
+            peers(ChessMove::Knight, 40)
+        );
+        assert_eq!(
+            vec![30, 31, 32, 39, 41, 48, 49, 50],
+            peers(ChessMove::King, 40)
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use super::{
+    ArrGridRowMajor, CellIdx, ColIdx, Digit, GridMutWithDefault, RowIdx, SizedCellIdx, DIM,
+};
+use std::ops::{Index, IndexMut};
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum CompositeLayoutError {
+    #[error("composite puzzle needs at least one grid")]
+    Empty,
+    #[error("grids must start on a multiple of 3 rows and columns")]
+    NotAligned,
+    #[error("two grids are at the same place")]
+    Repeated,
+    #[error("grids must overlap into a single puzzle")]
+    Disconnected,
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum CompositeGridError {
+    #[error("expected one grid per grid of the layout")]
+    Count,
+    #[error("grids disagree on shared cell {0:?}")]
+    Inconsistent(SizedCellIdx),
+}
+
+/// Placement of the 9x9 grids of a composite puzzle such as Samurai. Each grid is given by its
+/// top-left cell. Grids start on multiples of 3 rows and columns, so overlapping ones share whole
+/// boxes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompositeLayout {
+    origins: Vec<SizedCellIdx>,
+    height: usize,
+    width: usize,
+}
+
+impl CompositeLayout {
+    pub fn new(origins: Vec<SizedCellIdx>) -> Result<Self, CompositeLayoutError> {
+        if origins.is_empty() {
+            return Err(CompositeLayoutError::Empty);
+        }
+        if origins.iter().any(|x| x.row % 3 != 0 || x.col % 3 != 0) {
+            return Err(CompositeLayoutError::NotAligned);
+        }
+        if (1..origins.len()).any(|i| origins[..i].contains(&origins[i])) {
+            return Err(CompositeLayoutError::Repeated);
+        }
+        let t = Self {
+            height: origins.iter().map(|x| x.row + DIM).max().unwrap(),
+            width: origins.iter().map(|x| x.col + DIM).max().unwrap(),
+            origins,
+        };
+        if !t.is_connected() {
+            return Err(CompositeLayoutError::Disconnected);
+        }
+        Ok(t)
+    }
+
+    fn of(origins: &[(usize, usize)]) -> Self {
+        Self::new(
+            origins
+                .iter()
+                .map(|(row, col)| SizedCellIdx {
+                    row: *row,
+                    col: *col,
+                })
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    /// Four grids sharing a corner box with a fifth one in the middle.
+    pub fn samurai() -> Self {
+        Self::of(&[(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)])
+    }
+
+    /// Two grids sharing a corner box.
+    pub fn twodoku() -> Self {
+        Self::of(&[(0, 0), (6, 6)])
+    }
+
+    /// Four grids packed into a 12x12 square, each sharing 4 of its boxes with every other one.
+    pub fn butterfly() -> Self {
+        Self::of(&[(0, 0), (0, 3), (3, 0), (3, 3)])
+    }
+
+    /// Top-left cell of each grid.
+    pub fn origins(&self) -> &[SizedCellIdx] {
+        &self.origins
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Cell of the whole puzzle for cell `idx` of grid `grid`.
+    pub fn cell(&self, grid: usize, idx: CellIdx) -> SizedCellIdx {
+        let origin = self.origins[grid];
+        let (i, j): (usize, usize) = (idx.row.into(), idx.col.into());
+        SizedCellIdx {
+            row: origin.row + i,
+            col: origin.col + j,
+        }
+    }
+
+    /// Grids holding `idx`, with the cell `idx` is in each of them.
+    pub fn grids_of(&self, idx: SizedCellIdx) -> impl Iterator<Item = (usize, CellIdx)> + '_ {
+        self.origins
+            .iter()
+            .enumerate()
+            .filter_map(move |(grid, origin)| {
+                let row = RowIdx::try_from(idx.row.checked_sub(origin.row)?).ok()?;
+                let col = ColIdx::try_from(idx.col.checked_sub(origin.col)?).ok()?;
+                Some((grid, (row, col).into()))
+            })
+    }
+
+    pub fn contains(&self, idx: SizedCellIdx) -> bool {
+        self.grids_of(idx).next().is_some()
+    }
+
+    /// Cells of the puzzle in row-major order, each once even if shared.
+    pub fn iter_cells(&self) -> impl Iterator<Item = SizedCellIdx> + '_ {
+        (0..self.height)
+            .flat_map(move |row| (0..self.width).map(move |col| SizedCellIdx { row, col }))
+            .filter(|idx| self.contains(*idx))
+    }
+
+    fn row_major(&self, idx: SizedCellIdx) -> usize {
+        idx.row * self.width + idx.col
+    }
+
+    fn is_connected(&self) -> bool {
+        let overlap = |a: SizedCellIdx, b: SizedCellIdx| {
+            a.row.abs_diff(b.row) < DIM && a.col.abs_diff(b.col) < DIM
+        };
+        let mut seen = vec![false; self.origins.len()];
+        let mut stack = vec![0];
+        while let Some(grid) = stack.pop() {
+            if seen[grid] {
+                continue;
+            }
+            seen[grid] = true;
+            stack.extend(
+                (0..self.origins.len())
+                    .filter(|other| overlap(self.origins[grid], self.origins[*other])),
+            );
+        }
+        seen.into_iter().all(|x| x)
+    }
+}
+
+/// Composite puzzle of several 9x9 grids sharing boxes. A shared cell is stored once, so the
+/// grids always agree on it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CompositeGrid {
+    layout: CompositeLayout,
+    /// Digit of each cell of the bounding rectangle in row-major order, `None` outside grids.
+    cells: Vec<Option<Digit>>,
+}
+
+impl Index<SizedCellIdx> for CompositeGrid {
+    type Output = Option<Digit>;
+
+    fn index(&self, idx: SizedCellIdx) -> &Self::Output {
+        &self.cells[self.layout.row_major(idx)]
+    }
+}
+
+impl IndexMut<SizedCellIdx> for CompositeGrid {
+    /// Panics if `idx` is outside every grid.
+    fn index_mut(&mut self, idx: SizedCellIdx) -> &mut Self::Output {
+        assert!(self.layout.contains(idx));
+        &mut self.cells[self.layout.row_major(idx)]
+    }
+}
+
+impl CompositeGrid {
+    pub fn new(layout: CompositeLayout) -> Self {
+        Self {
+            cells: vec![None; layout.height * layout.width],
+            layout,
+        }
+    }
+
+    /// Merges grids solved or filled separately, one per grid of `layout`.
+    pub fn from_grids(
+        layout: CompositeLayout,
+        grids: &[ArrGridRowMajor],
+    ) -> Result<Self, CompositeGridError> {
+        if grids.len() != layout.origins.len() {
+            return Err(CompositeGridError::Count);
+        }
+        let mut t = Self::new(layout);
+        for (grid, src) in grids.iter().enumerate() {
+            for idx in CellIdx::iter_row_wise() {
+                let Some(value) = src[idx] else {
+                    continue;
+                };
+                let idx = t.layout.cell(grid, idx);
+                match t[idx] {
+                    Some(other) if other != value => {
+                        return Err(CompositeGridError::Inconsistent(idx))
+                    }
+                    _ => t[idx] = Some(value),
+                }
+            }
+        }
+        Ok(t)
+    }
+
+    pub fn layout(&self) -> &CompositeLayout {
+        &self.layout
+    }
+
+    /// Copy of grid `grid`.
+    pub fn grid(&self, grid: usize) -> ArrGridRowMajor {
+        ArrGridRowMajor::from_fn(|idx| self[self.layout.cell(grid, idx)])
+    }
+
+    pub fn iter_cells(&self) -> impl Iterator<Item = (SizedCellIdx, Option<Digit>)> + '_ {
+        self.layout.iter_cells().map(|idx| (idx, self[idx]))
+    }
+
+    pub fn iter_set(&self) -> impl Iterator<Item = (SizedCellIdx, Digit)> + '_ {
+        self.iter_cells()
+            .filter_map(|(idx, value)| Some((idx, value?)))
+    }
+
+    pub fn iter_unset(&self) -> impl Iterator<Item = SizedCellIdx> + '_ {
+        self.iter_cells()
+            .filter_map(|(idx, value)| value.is_none().then_some(idx))
+    }
+}
+
+impl std::fmt::Debug for CompositeGrid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = crate::format::CompositeAscii::default().write_string(self);
+        f.write_str(&s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CompositeGrid, CompositeGridError, CompositeLayout, CompositeLayoutError};
+    use crate::grid::{ArrGridRowMajor, CellIdx, Digit, SizedCellIdx};
+
+    fn at(row: usize, col: usize) -> SizedCellIdx {
+        SizedCellIdx { row, col }
+    }
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(
+            Err(CompositeLayoutError::Empty),
+            CompositeLayout::new(vec![])
+        );
+        assert_eq!(
+            Err(CompositeLayoutError::NotAligned),
+            CompositeLayout::new(vec![at(0, 0), at(4, 6)])
+        );
+        assert_eq!(
+            Err(CompositeLayoutError::Repeated),
+            CompositeLayout::new(vec![at(0, 0), at(0, 0)])
+        );
+        assert_eq!(
+            Err(CompositeLayoutError::Disconnected),
+            CompositeLayout::new(vec![at(0, 0), at(9, 9)])
+        );
+
+        let samurai = CompositeLayout::samurai();
+        assert_eq!((21, 21), (samurai.height(), samurai.width()));
+        assert_eq!(5 * 81 - 4 * 9, samurai.iter_cells().count());
+        assert!(!samurai.contains(at(0, 10)));
+        let top_left = CellIdx::try_of_row_major(0).unwrap();
+        assert_eq!(
+            vec![(0, CellIdx::try_of_row_major(60).unwrap()), (2, top_left)],
+            samurai.grids_of(at(6, 6)).collect::<Vec<_>>()
+        );
+        assert_eq!(at(12, 12), samurai.cell(4, top_left));
+        assert_eq!(2 * 81 - 9, CompositeLayout::twodoku().iter_cells().count());
+        assert_eq!(144, CompositeLayout::butterfly().iter_cells().count());
+    }
+
+    #[test]
+    fn test_from_grids() {
+        let layout = CompositeLayout::twodoku();
+        let mut first = ArrGridRowMajor::new();
+        let mut second = ArrGridRowMajor::new();
+        first[CellIdx::try_of_row_major(80).unwrap()] = Some(Digit::D5);
+        second[CellIdx::try_of_row_major(20).unwrap()] = Some(Digit::D5);
+        second[CellIdx::try_of_row_major(21).unwrap()] = Some(Digit::D7);
+        let grid = CompositeGrid::from_grids(layout.clone(), &[first, second]).unwrap();
+        assert_eq!(
+            vec![(at(8, 8), Digit::D5), (at(8, 9), Digit::D7)],
+            grid.iter_set().collect::<Vec<_>>()
+        );
+        assert_eq!(second, grid.grid(1));
+        assert_eq!(
+            Some(Digit::D5),
+            grid.grid(0)[CellIdx::try_of_row_major(80).unwrap()]
+        );
+
+        first[CellIdx::try_of_row_major(80).unwrap()] = Some(Digit::D6);
+        assert_eq!(
+            Err(CompositeGridError::Inconsistent(at(8, 8))),
+            CompositeGrid::from_grids(layout.clone(), &[first, second])
+        );
+        assert_eq!(
+            Err(CompositeGridError::Count),
+            CompositeGrid::from_grids(layout, &[first])
+        );
+    }
+}
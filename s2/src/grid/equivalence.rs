@@ -0,0 +1,106 @@
+use super::Grid;
+use crate::transform::{canonicalize, canonicalize_all, Transformation};
+
+/// Returns a transformation mapping `a` onto `b` if there is one, that is if both are the same
+/// grid up to row, column, band and stack permutations, transposition and digit relabeling.
+pub fn are_equivalent<T, U>(a: &T, b: &U) -> Option<Transformation>
+where
+    T: Grid + ?Sized,
+    U: Grid + ?Sized,
+{
+    let (canonical_a, to_canonical_a) = canonicalize(a);
+    let (canonical_b, to_canonical_b) = canonicalize(b);
+    (canonical_a == canonical_b).then(|| to_canonical_a.then(&to_canonical_b.inverse()))
+}
+
+/// Every transformation mapping `grid` onto itself, including the identity. Digits absent from
+/// `grid` stay fixed, otherwise a sparse puzzle would have too many to list.
+pub fn automorphisms<T>(grid: &T) -> Vec<Transformation>
+where
+    T: Grid + ?Sized,
+{
+    let (_, to_canonical) = canonicalize_all(grid);
+    let from_canonical = to_canonical[0].inverse();
+    to_canonical
+        .iter()
+        .map(|t| t.then(&from_canonical))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{are_equivalent, automorphisms};
+    use crate::format::{read_from_string, RowMajorAscii};
+    use crate::grid::{ArrGridRowMajor, CellIdx, Digit};
+    use crate::transform::Transformation;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn read(grid: &str) -> ArrGridRowMajor {
+        read_from_string(&RowMajorAscii::default(), grid.trim()).unwrap()
+    }
+
+    fn solution() -> ArrGridRowMajor {
+        read(
+            r#"
+534678912
+672195348
+198342567
+859761423
+426853791
+713924856
+961537284
+287419635
+345286179
+"#,
+        )
+    }
+
+    #[test]
+    fn test_equivalent() {
+        let grid = solution();
+        let mut rng = StdRng::seed_from_u64(0);
+        (0..4).for_each(|_| {
+            let disguised: ArrGridRowMajor = Transformation::random(&mut rng).apply(&grid);
+            let t = are_equivalent(&grid, &disguised).unwrap();
+            assert_eq!(disguised, t.apply::<_, ArrGridRowMajor>(&grid));
+        });
+    }
+
+    #[test]
+    fn test_not_equivalent() {
+        let grid = solution();
+        let mut puzzle = grid;
+        puzzle[CellIdx::default()] = None;
+        assert_eq!(None, are_equivalent(&grid, &puzzle));
+        let mut other = puzzle;
+        other[CellIdx::default()] = Some(Digit::D1);
+        assert_eq!(None, are_equivalent(&puzzle, &other));
+    }
+
+    #[test]
+    fn test_automorphisms() {
+        let grid = solution();
+        assert_eq!(vec![Transformation::identity()], automorphisms(&grid));
+
+        let grid = read(
+            r#"
+123456789
+456789123
+789123456
+234567891
+567891234
+891234567
+345678912
+678912345
+912345678
+"#,
+        );
+        let automorphisms = automorphisms(&grid);
+        assert!(automorphisms.len() > 1);
+        assert!(automorphisms.contains(&Transformation::identity()));
+        automorphisms
+            .iter()
+            .for_each(|t| assert_eq!(grid, t.apply::<_, ArrGridRowMajor>(&grid)));
+    }
+}
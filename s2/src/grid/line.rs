@@ -0,0 +1,230 @@
+use super::{CellIdx, DIM};
+use itertools::Itertools;
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum LineError {
+    #[error("line must have at least 2 cells")]
+    TooShort,
+    #[error("thermometer must have at most 9 cells")]
+    TooLong,
+    #[error("line has a repeated cell")]
+    RepeatedCell,
+    #[error("consecutive cells of a line must touch")]
+    NotConnected,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    /// Digits strictly increase from the bulb, the first cell.
+    Thermometer,
+    /// Digits on the arrow add up to the digit in the circle, the first cell.
+    Arrow,
+    /// Digits read the same in both directions.
+    Palindrome,
+}
+
+/// Line drawn through cells, each touching the previous one by a side or a corner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    kind: LineKind,
+    cells: Vec<CellIdx>,
+}
+
+impl Line {
+    pub fn new(kind: LineKind, cells: Vec<CellIdx>) -> Result<Self, LineError> {
+        if cells.len() < 2 {
+            return Err(LineError::TooShort);
+        }
+        if kind == LineKind::Thermometer && cells.len() > DIM {
+            return Err(LineError::TooLong);
+        }
+        if !cells.iter().all_unique() {
+            return Err(LineError::RepeatedCell);
+        }
+        let touch = |a: &CellIdx, b: &CellIdx| {
+            let (ai, aj): (usize, usize) = (a.row.into(), a.col.into());
+            let (bi, bj): (usize, usize) = (b.row.into(), b.col.into());
+            ai.abs_diff(bi) <= 1 && aj.abs_diff(bj) <= 1
+        };
+        if !cells.iter().tuple_windows().all(|(a, b)| touch(a, b)) {
+            return Err(LineError::NotConnected);
+        }
+        Ok(Self { kind, cells })
+    }
+
+    pub fn thermometer(cells: Vec<CellIdx>) -> Result<Self, LineError> {
+        Self::new(LineKind::Thermometer, cells)
+    }
+
+    /// `circle` followed by the cells of the arrow from its tail.
+    pub fn arrow(circle: CellIdx, cells: Vec<CellIdx>) -> Result<Self, LineError> {
+        Self::new(LineKind::Arrow, [vec![circle], cells].concat())
+    }
+
+    pub fn palindrome(cells: Vec<CellIdx>) -> Result<Self, LineError> {
+        Self::new(LineKind::Palindrome, cells)
+    }
+
+    pub fn kind(&self) -> LineKind {
+        self.kind
+    }
+
+    pub fn cells(&self) -> &[CellIdx] {
+        &self.cells
+    }
+
+    /// Digits the `pos`-th cell can take given the other cells of the line, one bit per digit.
+    /// `candidates_of` gives the digits each other cell can still take in the same form, a single
+    /// one for placed digits, so bounds follow from their smallest and largest candidates.
+    pub(crate) fn allowed<F>(&self, pos: usize, candidates_of: F) -> u16
+    where
+        F: Fn(CellIdx) -> u16,
+    {
+        let all = (1 << DIM) - 1;
+        let candidates_at = |i: usize| candidates_of(self.cells[i]) & all;
+        // Smallest and largest candidates from 1 to 9, `None` if there are none.
+        let bounds_at = |i: usize| match candidates_at(i) {
+            0 => None,
+            mask => Some((
+                mask.trailing_zeros() as i32 + 1,
+                16 - mask.leading_zeros() as i32,
+            )),
+        };
+        let others = (0..self.cells.len()).filter(|i| *i != pos);
+        let Some(bounds) = others
+            .map(|i| Some((i, bounds_at(i)?)))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return 0;
+        };
+        let len = self.cells.len() as i32;
+        let dim = DIM as i32;
+        let (lo, hi) = match self.kind {
+            LineKind::Thermometer => {
+                let p = pos as i32;
+                bounds
+                    .iter()
+                    .fold((p + 1, dim - (len - 1 - p)), |(lo, hi), (i, (min, max))| {
+                        let i = *i as i32;
+                        if i < p {
+                            (lo.max(min + p - i), hi)
+                        } else {
+                            (lo, hi.min(max - (i - p)))
+                        }
+                    })
+            }
+            LineKind::Arrow => {
+                let (min, max) = bounds
+                    .iter()
+                    .filter(|(i, _)| *i != 0)
+                    .fold((0, 0), |(lo, hi), (_, (min, max))| (lo + min, hi + max));
+                match bounds.first() {
+                    Some((0, (circle_min, circle_max))) => (circle_min - max, circle_max - min),
+                    _ => (min, max),
+                }
+            }
+            LineKind::Palindrome => {
+                let mirror = self.cells.len() - 1 - pos;
+                match mirror != pos {
+                    true => return candidates_at(mirror),
+                    false => (1, dim),
+                }
+            }
+        };
+        (lo.max(1)..=hi.min(dim)).fold(0, |acc, v| acc | 1 << (v - 1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Line, LineError};
+    use crate::grid::{CellIdx, ColIdx, RowIdx};
+    use std::collections::HashMap;
+
+    fn idx(i: usize, j: usize) -> CellIdx {
+        (RowIdx::try_from(i).unwrap(), ColIdx::try_from(j).unwrap()).into()
+    }
+
+    /// Digits `line` leaves at `pos` given the candidates of some cells, the others taking any.
+    fn allowed(line: &Line, pos: usize, candidates: &[(CellIdx, &[u8])]) -> Vec<u8> {
+        let candidates = candidates
+            .iter()
+            .map(|(idx, values)| (*idx, values.iter().fold(0, |acc, v| acc | 1 << (v - 1))))
+            .collect::<HashMap<_, u16>>();
+        let mask = line.allowed(pos, |idx| candidates.get(&idx).copied().unwrap_or(0x1ff));
+        (1..=9).filter(|v| mask & (1 << (v - 1)) != 0).collect()
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(Err(LineError::TooShort), Line::thermometer(vec![idx(0, 0)]));
+        assert_eq!(
+            Err(LineError::TooLong),
+            Line::thermometer((0..9).map(|j| idx(0, j)).chain([idx(1, 8)]).collect())
+        );
+        assert_eq!(
+            Err(LineError::RepeatedCell),
+            Line::palindrome(vec![idx(0, 0), idx(0, 1), idx(0, 0)])
+        );
+        assert_eq!(
+            Err(LineError::NotConnected),
+            Line::arrow(idx(0, 0), vec![idx(0, 2)])
+        );
+        assert!(Line::arrow(idx(0, 0), vec![idx(1, 1), idx(2, 1)]).is_ok());
+    }
+
+    #[test]
+    fn test_allowed() {
+        let thermo = Line::thermometer(vec![idx(0, 0), idx(0, 1), idx(0, 2), idx(1, 2)]).unwrap();
+        assert_eq!(vec![2, 3, 4, 5, 6, 7], allowed(&thermo, 1, &[]));
+        assert_eq!(vec![6, 7, 8], allowed(&thermo, 2, &[(idx(0, 0), &[4])]));
+        assert_eq!(
+            vec![5],
+            allowed(&thermo, 2, &[(idx(0, 1), &[4]), (idx(1, 2), &[6])])
+        );
+
+        let arrow = Line::arrow(idx(4, 4), vec![idx(4, 5), idx(4, 6), idx(4, 7)]).unwrap();
+        assert_eq!(vec![3, 4, 5, 6, 7, 8, 9], allowed(&arrow, 0, &[]));
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7], allowed(&arrow, 1, &[]));
+        assert_eq!(
+            vec![1, 2, 3],
+            allowed(&arrow, 1, &[(idx(4, 4), &[6]), (idx(4, 6), &[2])])
+        );
+        assert_eq!(
+            vec![8],
+            allowed(
+                &arrow,
+                0,
+                &[(idx(4, 5), &[1]), (idx(4, 6), &[2]), (idx(4, 7), &[5])]
+            )
+        );
+
+        let palindrome = Line::palindrome(vec![idx(0, 0), idx(1, 1), idx(2, 1)]).unwrap();
+        assert_eq!(vec![3], allowed(&palindrome, 2, &[(idx(0, 0), &[3])]));
+        assert_eq!(9, allowed(&palindrome, 1, &[(idx(0, 0), &[3])]).len());
+    }
+
+    #[test]
+    fn test_allowed_candidates() {
+        // Unset neighbours narrow the line through their smallest and largest candidates.
+        let thermo = Line::thermometer(vec![idx(0, 0), idx(0, 1), idx(0, 2), idx(1, 2)]).unwrap();
+        assert_eq!(vec![6, 7, 8], allowed(&thermo, 2, &[(idx(0, 1), &[5, 6])]));
+        assert_eq!(vec![1, 2], allowed(&thermo, 0, &[(idx(0, 1), &[2, 3])]));
+        assert!(allowed(&thermo, 1, &[(idx(0, 0), &[8, 9])]).is_empty());
+
+        let arrow = Line::arrow(idx(4, 4), vec![idx(4, 5), idx(4, 6)]).unwrap();
+        assert_eq!(
+            vec![1, 2],
+            allowed(&arrow, 1, &[(idx(4, 4), &[3, 4]), (idx(4, 6), &[2, 3])])
+        );
+        assert_eq!(
+            vec![4, 5, 6, 7],
+            allowed(&arrow, 0, &[(idx(4, 5), &[1, 3]), (idx(4, 6), &[3, 4])])
+        );
+        assert!(allowed(&arrow, 1, &[(idx(4, 6), &[])]).is_empty());
+
+        let palindrome = Line::palindrome(vec![idx(0, 0), idx(1, 1), idx(2, 1)]).unwrap();
+        assert_eq!(vec![2, 7], allowed(&palindrome, 0, &[(idx(2, 1), &[2, 7])]));
+    }
+}
@@ -8,13 +8,53 @@ use strum_macros::{EnumCount as EnumCountMacro, EnumIter as EnumIterMacro};
 use thiserror::Error;
 
 mod arr_grid;
-pub type ArrGridRowMajor = arr_grid::ArrGrid<true>;
-pub type ArrGridColMajor = arr_grid::ArrGrid<false>;
+pub type ArrGridRowMajor<S = Shape9x9> = arr_grid::ArrGrid<true, S>;
+pub type ArrGridColMajor<S = Shape9x9> = arr_grid::ArrGrid<false, S>;
+
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "serde")]
+pub use serde_impls::arr_grid_nested;
+
+mod cage;
+pub use cage::{sum_combinations, Cage, CageError};
+
+mod region;
+pub use region::{Region, RegionError};
+
+mod box_layout;
+pub use box_layout::{BoxLayout, BoxLayoutError};
+
+mod chess_move;
+pub use chess_move::ChessMove;
+
+mod line;
+pub use line::{Line, LineError, LineKind};
+
+mod pair;
+pub use pair::{Pair, PairError, Relation};
+
+mod parity;
+pub use parity::Parity;
+
+mod outside_clue;
+pub use outside_clue::{ClueKind, OutsideClue, OutsideClueError, Side};
+
+mod composite;
+pub use composite::{CompositeGrid, CompositeGridError, CompositeLayout, CompositeLayoutError};
+
+mod equivalence;
+pub use equivalence::{are_equivalent, automorphisms};
+
+mod sized;
+pub use sized::{
+    BoxShape, Shape, Shape16x16, Shape25x25, Shape4x4, Shape6x6, Shape9x9, SizedCellIdx,
+};
 
 pub const DIM: usize = 9;
 
 #[derive(
-    Debug, Default, Clone, Copy, EnumIterMacro, EnumCountMacro, PartialEq, Eq, PartialOrd, Ord,
+    Debug, Default, Clone, Copy, EnumIterMacro, EnumCountMacro, PartialEq, Eq, PartialOrd, Ord, Hash,
 )]
 pub enum RowIdx {
     #[default]
@@ -97,7 +137,7 @@ impl From<RowIdx> for usize {
 }
 
 #[derive(
-    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, EnumIterMacro, EnumCountMacro,
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIterMacro, EnumCountMacro,
 )]
 pub enum ColIdx {
     #[default]
@@ -180,7 +220,7 @@ impl From<ColIdx> for usize {
 }
 
 #[derive(
-    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, EnumIterMacro, EnumCountMacro,
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIterMacro, EnumCountMacro,
 )]
 pub enum Digit {
     #[default]
@@ -215,7 +255,7 @@ mod grid_value_ascii {
         let expected = Digit::iter().collect::<Vec<_>>();
         let actual = expected
             .iter()
-            .map(|x| Digit::as_ascii(&x))
+            .map(Digit::as_ascii)
             .map(|x| Digit::try_from_ascii(x).unwrap())
             .collect::<Vec<_>>();
         assert_eq!(&expected, &actual);
@@ -312,7 +352,8 @@ impl std::fmt::Display for Digit {
     }
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CellIdx {
     pub row: RowIdx,
     pub col: ColIdx,
@@ -321,8 +362,8 @@ pub struct CellIdx {
 impl From<&(RowIdx, ColIdx)> for CellIdx {
     fn from(v: &(RowIdx, ColIdx)) -> Self {
         Self {
-            row: v.0.clone(),
-            col: v.1.clone(),
+            row: v.0,
+            col: v.1,
         }
     }
 }
@@ -336,7 +377,7 @@ impl From<(RowIdx, ColIdx)> for CellIdx {
 impl From<(&RowIdx, ColIdx)> for CellIdx {
     fn from(v: (&RowIdx, ColIdx)) -> Self {
         Self {
-            row: v.0.clone(),
+            row: *v.0,
             col: v.1,
         }
     }
@@ -346,14 +387,14 @@ impl From<(RowIdx, &ColIdx)> for CellIdx {
     fn from(v: (RowIdx, &ColIdx)) -> Self {
         Self {
             row: v.0,
-            col: v.1.clone(),
+            col: *v.1,
         }
     }
 }
 
 impl From<&CellIdx> for (RowIdx, ColIdx) {
     fn from(v: &CellIdx) -> Self {
-        (v.row.clone(), v.col.clone())
+        (v.row, v.col)
     }
 }
 
@@ -398,6 +439,7 @@ impl CellIdx {
         Ok((i, j).into())
     }
 
+    /// Box of the classic layout, see `BoxLayout` for jigsaw sudoku.
     pub fn box_(&self) -> usize {
         let i: usize = self.row.into();
         let j: usize = self.col.into();
@@ -417,6 +459,7 @@ impl CellIdx {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GridDiff {
     Set(CellIdx, Digit),
     Unset(CellIdx),
@@ -605,6 +648,6 @@ pub fn fmt<T>(this: &T, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
 where
     T: Grid,
 {
-    let s = crate::format::write_string(&crate::format::RowMajorAscii::default(), this);
+    let s = crate::format::write_string(&crate::format::BoxDrawing::default(), this);
     f.write_str(&s)
 }
@@ -0,0 +1,241 @@
+use super::{CellIdx, ColIdx, RowIdx, DIM};
+use strum_macros::EnumIter as EnumIterMacro;
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum OutsideClueError {
+    #[error("clue must be on a row or column from 0 to 8")]
+    Index,
+    #[error("no row or column satisfies the clue")]
+    Infeasible,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIterMacro)]
+pub enum ClueKind {
+    /// Sum of the digits between the 1 and the 9.
+    Sandwich,
+    /// Number of digits seen from the clue, taller digits hiding the shorter ones behind them.
+    Skyscraper,
+    /// Sum of the first `x` digits seen from the clue, `x` being the first one.
+    XSum,
+}
+
+/// Side of the grid a clue is written on. Digits are read from that side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIterMacro)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// Clue written outside the grid about the row or column next to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutsideClue {
+    kind: ClueKind,
+    side: Side,
+    index: usize,
+    value: u8,
+}
+
+impl OutsideClue {
+    /// Clue on `side` of row or column `index`.
+    pub fn new(
+        kind: ClueKind,
+        side: Side,
+        index: usize,
+        value: u8,
+    ) -> Result<Self, OutsideClueError> {
+        if index >= DIM {
+            return Err(OutsideClueError::Index);
+        }
+        let t = Self {
+            kind,
+            side,
+            index,
+            value,
+        };
+        if !t.search(&[None; DIM], &mut |_| false) {
+            return Err(OutsideClueError::Infeasible);
+        }
+        Ok(t)
+    }
+
+    pub fn kind(&self) -> ClueKind {
+        self.kind
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+
+    /// Cells of the row or column in reading order from the clue.
+    pub fn cells(&self) -> [CellIdx; DIM] {
+        let at = |i: usize| -> CellIdx {
+            let (row, col) = match self.side {
+                Side::Top => (i, self.index),
+                Side::Bottom => (DIM - 1 - i, self.index),
+                Side::Left => (self.index, i),
+                Side::Right => (self.index, DIM - 1 - i),
+            };
+            (
+                RowIdx::try_from(row).unwrap(),
+                ColIdx::try_from(col).unwrap(),
+            )
+                .into()
+        };
+        std::array::from_fn(at)
+    }
+
+    /// Whether the clue may still hold once digits are appended to `prefix`, digits being from 1
+    /// to 9. A full line of distinct digits satisfies the clue if this holds.
+    fn may_hold(&self, prefix: &[u8]) -> bool {
+        let value = u32::from(self.value);
+        match self.kind {
+            ClueKind::Sandwich => {
+                let Some(start) = prefix.iter().position(|x| *x == 1 || *x == 9) else {
+                    return true;
+                };
+                let between = &prefix[start + 1..];
+                let end = between.iter().position(|x| *x == 1 || *x == 9);
+                let sum = between[..end.unwrap_or(between.len())]
+                    .iter()
+                    .map(|x| u32::from(*x))
+                    .sum::<u32>();
+                match end {
+                    Some(_) => sum == value,
+                    None => sum <= value,
+                }
+            }
+            ClueKind::Skyscraper => {
+                let (visible, tallest) = prefix.iter().fold((0, 0), |(visible, tallest), x| {
+                    if *x > tallest {
+                        (visible + 1, *x)
+                    } else {
+                        (visible, tallest)
+                    }
+                });
+                // Each digit left taller than the tallest so far may still be seen.
+                let taller = DIM as u32 - u32::from(tallest);
+                visible <= value && visible + taller >= value
+            }
+            ClueKind::XSum => {
+                let Some(x) = prefix.first() else {
+                    return true;
+                };
+                let x = usize::from(*x);
+                let sum = prefix.iter().take(x).map(|x| u32::from(*x)).sum::<u32>();
+                if prefix.len() >= x {
+                    sum == value
+                } else {
+                    sum < value
+                }
+            }
+        }
+    }
+
+    /// Calls `f` on every line of distinct digits from 1 to 9, in reading order, satisfying the
+    /// clue and agreeing with `placed`, until it returns `false`. Returns whether any was found.
+    pub(crate) fn search<F>(&self, placed: &[Option<u8>; DIM], f: &mut F) -> bool
+    where
+        F: FnMut(&[u8; DIM]) -> bool,
+    {
+        fn go<F>(
+            clue: &OutsideClue,
+            placed: &[Option<u8>; DIM],
+            line: &mut [u8; DIM],
+            len: usize,
+            used: u16,
+            found: &mut bool,
+            f: &mut F,
+        ) -> bool
+        where
+            F: FnMut(&[u8; DIM]) -> bool,
+        {
+            if !clue.may_hold(&line[..len]) {
+                return true;
+            }
+            if len == DIM {
+                *found = true;
+                return f(line);
+            }
+            let candidates = match placed[len] {
+                Some(x) => x..=x,
+                None => 1..=DIM as u8,
+            };
+            for x in candidates {
+                if used & (1 << x) != 0 {
+                    continue;
+                }
+                line[len] = x;
+                if !go(clue, placed, line, len + 1, used | 1 << x, found, f) {
+                    return false;
+                }
+            }
+            true
+        }
+
+        let mut found = false;
+        go(self, placed, &mut [0; DIM], 0, 0, &mut found, f);
+        found
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ClueKind, OutsideClue, OutsideClueError, Side};
+
+    fn holds(kind: ClueKind, value: u8, line: &str) -> bool {
+        let clue = OutsideClue::new(kind, Side::Left, 0, value).unwrap();
+        let placed = line.bytes().map(|x| Some(x - b'0')).collect::<Vec<_>>();
+        clue.search(&placed.try_into().unwrap(), &mut |_| false)
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(
+            Err(OutsideClueError::Index),
+            OutsideClue::new(ClueKind::Skyscraper, Side::Top, 9, 3)
+        );
+        assert_eq!(
+            Err(OutsideClueError::Infeasible),
+            OutsideClue::new(ClueKind::Skyscraper, Side::Top, 0, 10)
+        );
+        assert_eq!(
+            Err(OutsideClueError::Infeasible),
+            OutsideClue::new(ClueKind::Sandwich, Side::Top, 0, 1)
+        );
+        assert_eq!(
+            Err(OutsideClueError::Infeasible),
+            OutsideClue::new(ClueKind::XSum, Side::Top, 0, 2)
+        );
+        let clue = OutsideClue::new(ClueKind::XSum, Side::Bottom, 2, 45).unwrap();
+        assert_eq!(
+            vec![74, 65, 56, 47, 38, 29, 20, 11, 2],
+            clue.cells().map(|idx| idx.row_major()).to_vec()
+        );
+    }
+
+    #[test]
+    fn test_holds() {
+        assert!(holds(ClueKind::Sandwich, 0, "519234678"));
+        assert!(holds(ClueKind::Sandwich, 14, "123459678"));
+        assert!(!holds(ClueKind::Sandwich, 9, "123459678"));
+        assert!(holds(ClueKind::Skyscraper, 9, "123456789"));
+        assert!(holds(ClueKind::Skyscraper, 1, "912345678"));
+        assert!(holds(ClueKind::Skyscraper, 3, "712345689"));
+        assert!(!holds(ClueKind::Skyscraper, 4, "712345689"));
+        assert!(holds(ClueKind::XSum, 1, "123456789"));
+        assert!(holds(ClueKind::XSum, 9, "315267489"));
+        assert!(!holds(ClueKind::XSum, 10, "315267489"));
+        assert!(holds(ClueKind::XSum, 45, "912345678"));
+    }
+}
@@ -0,0 +1,247 @@
+use super::{CellIdx, ColIdx, RowIdx, DIM};
+use std::collections::HashSet;
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum PairError {
+    #[error("cells of a pair must share a side")]
+    NotAdjacent,
+}
+
+/// Relation between the digits of two cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    /// Kropki white dot.
+    Consecutive,
+    NotConsecutive,
+    /// Kropki black dot: one digit is twice the other.
+    Double,
+    NotDouble,
+    /// X for a sum of 10, V for a sum of 5.
+    Sum(u8),
+    NotSum(u8),
+    /// Greater-than sign pointing at the first cell: its digit is the smaller one.
+    Less,
+}
+
+impl Relation {
+    /// Whether digits `a` of the first cell and `b` of the second one, from 1 to 9, satisfy the
+    /// relation.
+    pub fn holds(self, a: u8, b: u8) -> bool {
+        match self {
+            Self::Consecutive => a.abs_diff(b) == 1,
+            Self::NotConsecutive => a.abs_diff(b) != 1,
+            Self::Double => a == 2 * b || b == 2 * a,
+            Self::NotDouble => a != 2 * b && b != 2 * a,
+            Self::Sum(sum) => a + b == sum,
+            Self::NotSum(sum) => a + b != sum,
+            Self::Less => a < b,
+        }
+    }
+
+    /// Digits the cell at `pos` of a pair can take, one bit per digit, given the digit of the
+    /// other cell if placed. Both cells share a row or a column, so their digits differ.
+    pub(crate) fn allowed(self, pos: usize, other: Option<u8>) -> u16 {
+        let holds = |a: u8, b: u8| match pos {
+            0 => self.holds(a, b),
+            _ => self.holds(b, a),
+        };
+        (1..=DIM as u8)
+            .filter(|a| match other {
+                Some(b) => *a != b && holds(*a, b),
+                None => (1..=DIM as u8).any(|b| *a != b && holds(*a, b)),
+            })
+            .fold(0, |acc, a| acc | 1 << (a - 1))
+    }
+}
+
+/// Two cells sharing a side whose digits satisfy a relation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pair {
+    cells: [CellIdx; 2],
+    relation: Relation,
+}
+
+/// Every pair of cells sharing a side, the left or top one first.
+fn adjacent() -> impl Iterator<Item = (CellIdx, CellIdx)> {
+    CellIdx::iter_row_wise().flat_map(|idx| {
+        let (i, j): (usize, usize) = (idx.row.into(), idx.col.into());
+        let right = ColIdx::try_from(j + 1).ok().map(|col| (idx.row, col));
+        let below = RowIdx::try_from(i + 1).ok().map(|row| (row, idx.col));
+        [right, below]
+            .into_iter()
+            .flatten()
+            .map(move |other| (idx, other.into()))
+    })
+}
+
+impl Pair {
+    pub fn new(a: CellIdx, b: CellIdx, relation: Relation) -> Result<Self, PairError> {
+        let (ai, aj): (usize, usize) = (a.row.into(), a.col.into());
+        let (bi, bj): (usize, usize) = (b.row.into(), b.col.into());
+        if ai.abs_diff(bi) + aj.abs_diff(bj) != 1 {
+            return Err(PairError::NotAdjacent);
+        }
+        Ok(Self {
+            cells: [a, b],
+            relation,
+        })
+    }
+
+    pub fn cells(&self) -> &[CellIdx] {
+        &self.cells
+    }
+
+    pub fn relation(&self) -> Relation {
+        self.relation
+    }
+
+    /// Pairs for the dots of a puzzle, the ones without a dot getting `absent` when `all_given`.
+    fn dotted(
+        dots: &[(&[(CellIdx, CellIdx)], Relation)],
+        absent: &[Relation],
+        all_given: bool,
+    ) -> Result<Vec<Self>, PairError> {
+        let mut pairs = dots
+            .iter()
+            .flat_map(|(cells, relation)| cells.iter().map(|(a, b)| Self::new(*a, *b, *relation)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if all_given {
+            let dotted = pairs
+                .iter()
+                .map(|pair| {
+                    (
+                        pair.cells[0].min(pair.cells[1]),
+                        pair.cells[0].max(pair.cells[1]),
+                    )
+                })
+                .collect::<HashSet<_>>();
+            let missing = adjacent()
+                .filter(|cells| !dotted.contains(cells))
+                .flat_map(|(a, b)| {
+                    absent
+                        .iter()
+                        .map(move |relation| Self::new(a, b, *relation))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            pairs.extend(missing);
+        }
+        Ok(pairs)
+    }
+
+    /// Kropki white dots between consecutive digits and black dots between a digit and its
+    /// double. With `all_given`, cells sharing a side without a dot have neither relation.
+    pub fn kropki(
+        white: &[(CellIdx, CellIdx)],
+        black: &[(CellIdx, CellIdx)],
+        all_given: bool,
+    ) -> Result<Vec<Self>, PairError> {
+        Self::dotted(
+            &[(white, Relation::Consecutive), (black, Relation::Double)],
+            &[Relation::NotConsecutive, Relation::NotDouble],
+            all_given,
+        )
+    }
+
+    /// X between digits adding up to 10 and V between digits adding up to 5. With `all_given`,
+    /// cells sharing a side without a mark add up to neither.
+    pub fn xv(
+        x: &[(CellIdx, CellIdx)],
+        v: &[(CellIdx, CellIdx)],
+        all_given: bool,
+    ) -> Result<Vec<Self>, PairError> {
+        Self::dotted(
+            &[(x, Relation::Sum(10)), (v, Relation::Sum(5))],
+            &[Relation::NotSum(10), Relation::NotSum(5)],
+            all_given,
+        )
+    }
+
+    /// Greater-than signs, each between a smaller digit and a larger one, as `(smaller, larger)`.
+    pub fn greater_than(signs: &[(CellIdx, CellIdx)]) -> Result<Vec<Self>, PairError> {
+        signs
+            .iter()
+            .map(|(smaller, larger)| Self::new(*smaller, *larger, Relation::Less))
+            .collect()
+    }
+
+    /// No consecutive digits in cells sharing a side.
+    pub fn non_consecutive() -> Vec<Self> {
+        adjacent()
+            .map(|(a, b)| Self::new(a, b, Relation::NotConsecutive).unwrap())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Pair, PairError, Relation};
+    use crate::grid::CellIdx;
+
+    fn idx(x: usize) -> CellIdx {
+        CellIdx::try_of_row_major(x).unwrap()
+    }
+
+    fn digits(mask: u16) -> Vec<u8> {
+        (1..=9).filter(|a| mask & (1 << (a - 1)) != 0).collect()
+    }
+
+    #[test]
+    fn test_allowed() {
+        assert_eq!(
+            vec![1, 2, 3, 4, 6, 8],
+            digits(Relation::Double.allowed(0, None))
+        );
+        assert_eq!(vec![2, 8], digits(Relation::Double.allowed(0, Some(4))));
+        assert_eq!(
+            vec![2, 4],
+            digits(Relation::Consecutive.allowed(1, Some(3)))
+        );
+        assert_eq!(
+            vec![1, 2, 3, 4, 6, 7, 8, 9],
+            digits(Relation::Sum(10).allowed(0, None))
+        );
+        assert_eq!(vec![1, 2, 3, 4], digits(Relation::Sum(5).allowed(1, None)));
+        assert_eq!(
+            vec![1, 2, 3, 7, 8, 9],
+            digits(Relation::NotConsecutive.allowed(0, Some(5)))
+        );
+        assert_eq!(
+            vec![1, 2, 3, 4, 5, 6, 7, 8],
+            digits(Relation::Less.allowed(0, None))
+        );
+        assert_eq!(
+            vec![5, 6, 7, 8, 9],
+            digits(Relation::Less.allowed(1, Some(4)))
+        );
+        assert_eq!(vec![1, 2, 3], digits(Relation::Less.allowed(0, Some(4))));
+    }
+
+    #[test]
+    fn test_pairs() {
+        assert_eq!(
+            Err(PairError::NotAdjacent),
+            Pair::new(idx(0), idx(10), Relation::Double)
+        );
+        assert_eq!(144, Pair::non_consecutive().len());
+        assert_eq!(
+            2,
+            Pair::kropki(&[(idx(0), idx(1))], &[(idx(9), idx(0))], false)
+                .unwrap()
+                .len()
+        );
+        let pairs = Pair::kropki(&[(idx(0), idx(1))], &[(idx(9), idx(0))], true).unwrap();
+        assert_eq!(2 + 2 * 142, pairs.len());
+        let on = |a, b| {
+            pairs
+                .iter()
+                .filter(|pair| pair.cells().contains(&idx(a)) && pair.cells().contains(&idx(b)))
+                .count()
+        };
+        assert_eq!((1, 1, 2), (on(0, 1), on(0, 9), on(1, 2)));
+        assert_eq!(2 * 144, Pair::xv(&[], &[], true).unwrap().len());
+        let signs = Pair::greater_than(&[(idx(1), idx(0))]).unwrap();
+        assert_eq!(&[idx(1), idx(0)], signs[0].cells());
+        assert!(signs[0].relation().holds(2, 7) && !signs[0].relation().holds(7, 2));
+    }
+}
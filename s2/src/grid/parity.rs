@@ -0,0 +1,47 @@
+use super::Digit;
+
+/// Shading of a cell restricting it to odd or to even digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    Odd,
+    Even,
+}
+
+impl Parity {
+    pub fn holds(self, value: Digit) -> bool {
+        // `Digit::D1` is 0, so odd digits have even indices.
+        let odd = u8::from(value) % 2 == 0;
+        odd == (self == Self::Odd)
+    }
+
+    /// Digits of the parity, bit `d` standing for digit `d + 1`.
+    pub(crate) fn mask(self) -> u16 {
+        match self {
+            Self::Odd => 0b1_0101_0101,
+            Self::Even => 0b0_1010_1010,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Parity;
+    use crate::grid::Digit;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_holds() {
+        let digits = |parity: Parity| {
+            Digit::iter()
+                .filter(|value| parity.holds(*value))
+                .map(|value| u8::from(value) + 1)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![1, 3, 5, 7, 9], digits(Parity::Odd));
+        assert_eq!(vec![2, 4, 6, 8], digits(Parity::Even));
+        for parity in [Parity::Odd, Parity::Even] {
+            assert!(Digit::iter()
+                .all(|value| (parity.mask() & (1 << u8::from(value)) != 0) == parity.holds(value)));
+        }
+    }
+}
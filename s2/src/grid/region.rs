@@ -0,0 +1,105 @@
+use super::{CellIdx, ColIdx, RowIdx, DIM};
+use itertools::Itertools;
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum RegionError {
+    #[error("region must have exactly 9 cells")]
+    Size,
+    #[error("region has a repeated cell")]
+    RepeatedCell,
+}
+
+/// Extra set of 9 cells that must hold every digit once, on top of rows, columns and boxes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    cells: Vec<CellIdx>,
+}
+
+fn idx(i: usize, j: usize) -> CellIdx {
+    (RowIdx::try_from(i).unwrap(), ColIdx::try_from(j).unwrap()).into()
+}
+
+impl Region {
+    pub fn new(cells: Vec<CellIdx>) -> Result<Self, RegionError> {
+        if cells.len() != DIM {
+            return Err(RegionError::Size);
+        }
+        if !cells.iter().all_unique() {
+            return Err(RegionError::RepeatedCell);
+        }
+        Ok(Self { cells })
+    }
+
+    pub fn cells(&self) -> &[CellIdx] {
+        &self.cells
+    }
+
+    /// Both main diagonals, as in Sudoku-X.
+    pub fn diagonals() -> Vec<Self> {
+        vec![
+            Self::new((0..DIM).map(|i| idx(i, i)).collect()).unwrap(),
+            Self::new((0..DIM).map(|i| idx(i, DIM - 1 - i)).collect()).unwrap(),
+        ]
+    }
+
+    /// The four 3x3 windows of Windoku, also known as Hyper Sudoku, with their top-left cells at
+    /// (1, 1), (1, 5), (5, 1) and (5, 5).
+    pub fn windows() -> Vec<Self> {
+        [1, 5]
+            .into_iter()
+            .cartesian_product([1, 5])
+            .map(|(top, left)| {
+                let cells = (0..3)
+                    .cartesian_product(0..3)
+                    .map(|(i, j)| idx(top + i, left + j))
+                    .collect();
+                Self::new(cells).unwrap()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Region, RegionError};
+    use crate::grid::CellIdx;
+
+    #[test]
+    fn test_new() {
+        let cells = CellIdx::iter_row_wise().take(9).collect::<Vec<_>>();
+        assert!(Region::new(cells.clone()).is_ok());
+        assert_eq!(Err(RegionError::Size), Region::new(cells[..8].to_vec()));
+        let mut repeated = cells;
+        repeated[8] = repeated[0];
+        assert_eq!(Err(RegionError::RepeatedCell), Region::new(repeated));
+    }
+
+    #[test]
+    fn test_predefined() {
+        let diagonals = Region::diagonals();
+        assert_eq!(2, diagonals.len());
+        assert!(diagonals[0]
+            .cells()
+            .iter()
+            .all(|idx| idx.row_major() % 10 == 0));
+        assert_eq!(
+            (0..9).map(|i| i * 8 + 8).collect::<Vec<_>>(),
+            diagonals[1]
+                .cells()
+                .iter()
+                .map(CellIdx::row_major)
+                .collect::<Vec<_>>()
+        );
+        let windows = Region::windows();
+        assert_eq!(4, windows.len());
+        assert_eq!(
+            vec![10, 11, 12, 19, 20, 21, 28, 29, 30],
+            windows[0]
+                .cells()
+                .iter()
+                .map(CellIdx::row_major)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use super::arr_grid::ArrGrid;
+use super::{CellIdx, ColIdx, Digit, Grid, GridMutWithDefault, RowIdx, DIM};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Digits are written as the numbers 1 to 9.
+impl Serialize for Digit {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(u8::from(self) + 1)
+    }
+}
+
+impl<'de> Deserialize<'de> for Digit {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u8::deserialize(deserializer)?;
+        value
+            .checked_sub(1)
+            .and_then(|x| Digit::try_from(x).ok())
+            .ok_or_else(|| de::Error::custom(format!("{value} is not a digit from 1 to 9")))
+    }
+}
+
+/// Rows and columns are written as the numbers 0 to 8.
+impl Serialize for RowIdx {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(usize::from(self) as u8)
+    }
+}
+
+impl<'de> Deserialize<'de> for RowIdx {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u8::deserialize(deserializer)?;
+        RowIdx::try_from(usize::from(value))
+            .map_err(|_| de::Error::custom(format!("{value} is not a row from 0 to 8")))
+    }
+}
+
+impl Serialize for ColIdx {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(usize::from(self) as u8)
+    }
+}
+
+impl<'de> Deserialize<'de> for ColIdx {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u8::deserialize(deserializer)?;
+        ColIdx::try_from(usize::from(value))
+            .map_err(|_| de::Error::custom(format!("{value} is not a column from 0 to 8")))
+    }
+}
+
+/// Reads a grid from either an 81-character string or 9 rows of 9 numbers.
+struct GridVisitor<G>(PhantomData<G>);
+
+impl<'de, G: GridMutWithDefault> Visitor<'de> for GridVisitor<G> {
+    type Value = G;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a string of 81 cells or 9 rows of 9 numbers from 0 to 9")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<G, E> {
+        if s.chars().count() != CellIdx::COUNT {
+            return Err(E::invalid_length(s.chars().count(), &self));
+        }
+        let mut grid = G::default();
+        for (idx, c) in CellIdx::iter_row_wise().zip(s.chars()) {
+            grid[idx] = match c {
+                '.' | '0' => None,
+                '1'..='9' => Some(Digit::try_from_ascii(c as u8).unwrap()),
+                _ => return Err(E::invalid_value(de::Unexpected::Char(c), &self)),
+            };
+        }
+        Ok(grid)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<G, A::Error> {
+        let mut grid = G::default();
+        let cells = CellIdx::iter_row_wise().collect::<Vec<_>>();
+        for (i, row) in cells.chunks(DIM).enumerate() {
+            let values = seq
+                .next_element::<[u8; DIM]>()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+            for (idx, value) in row.iter().zip(values) {
+                grid[*idx] = match value {
+                    0 => None,
+                    _ => Some(Digit::try_from(value - 1).map_err(|_| {
+                        de::Error::invalid_value(de::Unexpected::Unsigned(value.into()), &self)
+                    })?),
+                };
+            }
+        }
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(DIM + 1, &self));
+        }
+        Ok(grid)
+    }
+}
+
+/// Grids are written as a string of 81 cells row by row, `.` for empty cells. Reading takes
+/// `0` for empty cells as well, and the rows of `arr_grid_nested` too.
+impl<const ROW_MAJOR: bool> Serialize for ArrGrid<ROW_MAJOR>
+where
+    ArrGrid<ROW_MAJOR>: Grid,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let s = self
+            .iter_values_row_wise()
+            .map(|value| value.map_or('.', |x| x.as_ascii().into()))
+            .collect::<String>();
+        serializer.serialize_str(&s)
+    }
+}
+
+impl<'de, const ROW_MAJOR: bool> Deserialize<'de> for ArrGrid<ROW_MAJOR>
+where
+    ArrGrid<ROW_MAJOR>: GridMutWithDefault,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(GridVisitor(PhantomData))
+    }
+}
+
+/// Writes grids as 9 rows of 9 numbers, 0 for empty cells, for use with
+/// `#[serde(with = "s2::grid::arr_grid_nested")]`.
+pub mod arr_grid_nested {
+    use super::GridVisitor;
+    use crate::grid::{CellIdx, Grid, GridMutWithDefault, DIM};
+    use serde::ser::SerializeSeq;
+    use serde::{Deserializer, Serializer};
+    use std::marker::PhantomData;
+
+    pub fn serialize<G, S>(grid: &G, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        G: Grid + ?Sized,
+        S: Serializer,
+    {
+        let cells = CellIdx::iter_row_wise().collect::<Vec<_>>();
+        let mut seq = serializer.serialize_seq(Some(DIM))?;
+        for row in cells.chunks(DIM) {
+            let values: [u8; DIM] =
+                std::array::from_fn(|j| grid[row[j]].map_or(0, |x| u8::from(x) + 1));
+            seq.serialize_element(&values)?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, G, D>(deserializer: D) -> Result<G, D::Error>
+    where
+        G: GridMutWithDefault,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(GridVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod serde_test {
+    use crate::format::{read_from_string, RowMajorAscii};
+    use crate::grid::{ArrGridColMajor, ArrGridRowMajor, CellIdx, Digit, Grid, GridDiff};
+    use crate::solver::SolverError;
+    use crate::status::SudokuStatus;
+    use serde::{Deserialize, Serialize};
+
+    const SINGLE_LINE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    fn grid() -> ArrGridRowMajor {
+        read_from_string(
+            &RowMajorAscii::default(),
+            &SINGLE_LINE
+                .replace('.', "_")
+                .as_bytes()
+                .chunks(9)
+                .map(|row| std::str::from_utf8(row).unwrap())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_indices() {
+        let idx = CellIdx::try_of_row_major(14).unwrap();
+        let diffs = vec![GridDiff::Set(idx, Digit::D7), GridDiff::Unset(idx)];
+        let json = serde_json::to_string(&diffs).unwrap();
+        assert_eq!(
+            r#"[{"Set":[{"row":1,"col":5},7]},{"Unset":{"row":1,"col":5}}]"#,
+            json
+        );
+        assert_eq!(diffs, serde_json::from_str::<Vec<GridDiff>>(&json).unwrap());
+        assert!(serde_json::from_str::<Digit>("0").is_err());
+        assert!(serde_json::from_str::<CellIdx>(r#"{"row":9,"col":0}"#).is_err());
+    }
+
+    #[test]
+    fn test_arr_grid() {
+        let json = serde_json::to_string(&grid()).unwrap();
+        assert_eq!(format!("\"{SINGLE_LINE}\""), json);
+        assert_eq!(
+            grid(),
+            serde_json::from_str::<ArrGridColMajor>(&json).unwrap()
+        );
+        let zeros = json.replace('.', "0");
+        assert_eq!(
+            grid(),
+            serde_json::from_str::<ArrGridRowMajor>(&zeros).unwrap()
+        );
+        assert!(serde_json::from_str::<ArrGridRowMajor>(&json.replacen('.', "", 1)).is_err());
+        assert!(serde_json::from_str::<ArrGridRowMajor>(&json.replacen('.', "x", 1)).is_err());
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Nested {
+        #[serde(with = "crate::grid::arr_grid_nested")]
+        grid: ArrGridColMajor,
+    }
+
+    #[test]
+    fn test_arr_grid_nested() {
+        let nested = Nested {
+            grid: grid().copy_into(),
+        };
+        let json = serde_json::to_string(&nested).unwrap();
+        assert!(json.starts_with(r#"{"grid":[[5,3,0,0,7,0,0,0,0],[6,0,0,1,9,5,0,0,0],"#));
+        assert_eq!(nested, serde_json::from_str(&json).unwrap());
+        // The default form reads nested rows as well.
+        let rows = &json[8..json.len() - 1];
+        assert_eq!(
+            grid(),
+            serde_json::from_str::<ArrGridRowMajor>(rows).unwrap()
+        );
+        assert!(serde_json::from_str::<ArrGridRowMajor>(&rows.replacen('5', "10", 1)).is_err());
+        assert!(serde_json::from_str::<ArrGridRowMajor>(&rows[..rows.len() - 21]).is_err());
+    }
+
+    #[test]
+    fn test_status() {
+        assert_eq!(
+            r#"["Infeasible","Cancelled","ConstraintsViolated"]"#,
+            serde_json::to_string(&[
+                SolverError::Infeasible,
+                SolverError::Cancelled,
+                SolverError::ConstraintsViolated
+            ])
+            .unwrap()
+        );
+        assert_eq!(
+            SudokuStatus::Complete,
+            serde_json::from_str(r#""Complete""#).unwrap()
+        );
+        let res: Result<SudokuStatus, SolverError> =
+            serde_json::from_str(r#"{"Err":"Cancelled"}"#).unwrap();
+        assert_eq!(Err(SolverError::Cancelled), res);
+    }
+}
@@ -0,0 +1,200 @@
+use super::{CellIdx, ColIdx, Digit, RowIdx, DIM};
+
+/// Dimensions of a grid made of `BOX_WIDTH` by `BOX_HEIGHT` boxes. Such a grid has `DIM` rows,
+/// columns, boxes and digits.
+pub trait Shape: std::fmt::Debug + Default + Clone + Copy + PartialEq + Eq {
+    const BOX_WIDTH: usize;
+    const BOX_HEIGHT: usize;
+    const DIM: usize;
+    const COUNT: usize = Self::DIM * Self::DIM;
+
+    /// Digits of the cells, zero-based: `Digit` for `Shape9x9`, so that its grids are `Grid`s,
+    /// and `u8` for the others.
+    type Digit: Copy + Eq + std::fmt::Debug + Into<u8> + TryFrom<u8, Error: std::fmt::Debug>;
+
+    /// Cells of a grid: an array for `Shape9x9`, so that its grids stay `Copy`, and a boxed slice
+    /// for the others.
+    type Cells: AsRef<[Option<Self::Digit>]> + AsMut<[Option<Self::Digit>]> + Clone + Eq;
+
+    /// `COUNT` empty cells.
+    fn empty_cells() -> Self::Cells;
+
+    /// Digit of zero-based `value`, which must be below `DIM`.
+    fn digit(value: u8) -> Self::Digit {
+        value.try_into().unwrap()
+    }
+
+    /// Boxes are numbered row-major, `BOX_HEIGHT` of them side by side in each band.
+    fn box_(idx: SizedCellIdx) -> usize {
+        idx.row / Self::BOX_HEIGHT * Self::BOX_HEIGHT + idx.col / Self::BOX_WIDTH
+    }
+
+    fn iter_row_wise() -> impl Iterator<Item = SizedCellIdx> {
+        (0..Self::COUNT).map(|idx| SizedCellIdx::of_row_major::<Self>(idx))
+    }
+
+    fn iter_row(row: usize) -> impl Iterator<Item = SizedCellIdx> {
+        (0..Self::DIM).map(move |col| SizedCellIdx { row, col })
+    }
+
+    fn iter_col(col: usize) -> impl Iterator<Item = SizedCellIdx> {
+        (0..Self::DIM).map(move |row| SizedCellIdx { row, col })
+    }
+
+    fn iter_box(box_: usize) -> impl Iterator<Item = SizedCellIdx> {
+        let top = box_ / Self::BOX_HEIGHT * Self::BOX_HEIGHT;
+        let left = box_ % Self::BOX_HEIGHT * Self::BOX_WIDTH;
+        (0..Self::DIM).map(move |x| SizedCellIdx {
+            row: top + x / Self::BOX_WIDTH,
+            col: left + x % Self::BOX_WIDTH,
+        })
+    }
+
+    /// Rows, then columns, then boxes.
+    fn iter_units() -> impl Iterator<Item = Vec<SizedCellIdx>> {
+        (0..Self::DIM)
+            .map(|row| Self::iter_row(row).collect())
+            .chain((0..Self::DIM).map(|col| Self::iter_col(col).collect()))
+            .chain((0..Self::DIM).map(|box_| Self::iter_box(box_).collect()))
+    }
+}
+
+/// Boxes `WIDTH` cells wide and `HEIGHT` cells tall. Digits are kept in a `u32` bit set by the
+/// solver, so a grid has at most 32 of them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BoxShape<const WIDTH: usize, const HEIGHT: usize>;
+
+impl<const WIDTH: usize, const HEIGHT: usize> Shape for BoxShape<WIDTH, HEIGHT> {
+    const BOX_WIDTH: usize = WIDTH;
+    const BOX_HEIGHT: usize = HEIGHT;
+    const DIM: usize = {
+        assert!(WIDTH > 0 && HEIGHT > 0 && WIDTH * HEIGHT <= 32);
+        WIDTH * HEIGHT
+    };
+    type Digit = u8;
+    type Cells = Box<[Option<u8>]>;
+
+    fn empty_cells() -> Self::Cells {
+        vec![None; Self::COUNT].into_boxed_slice()
+    }
+}
+
+/// Classic sudoku, whose grids hold a `Digit` in each cell.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Shape9x9;
+
+impl Shape for Shape9x9 {
+    const BOX_WIDTH: usize = 3;
+    const BOX_HEIGHT: usize = 3;
+    const DIM: usize = DIM;
+    type Digit = Digit;
+    type Cells = [Option<Digit>; CellIdx::COUNT];
+
+    fn empty_cells() -> Self::Cells {
+        [None; CellIdx::COUNT]
+    }
+}
+
+pub type Shape4x4 = BoxShape<2, 2>;
+pub type Shape6x6 = BoxShape<3, 2>;
+pub type Shape16x16 = BoxShape<4, 4>;
+pub type Shape25x25 = BoxShape<5, 5>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SizedCellIdx {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl SizedCellIdx {
+    pub fn row_major<S: Shape>(&self) -> usize {
+        self.row * S::DIM + self.col
+    }
+
+    pub fn col_major<S: Shape>(&self) -> usize {
+        self.col * S::DIM + self.row
+    }
+
+    pub fn of_row_major<S: Shape>(idx: usize) -> Self {
+        Self {
+            row: idx / S::DIM,
+            col: idx % S::DIM,
+        }
+    }
+}
+
+impl From<CellIdx> for SizedCellIdx {
+    fn from(v: CellIdx) -> Self {
+        Self {
+            row: v.row.into(),
+            col: v.col.into(),
+        }
+    }
+}
+
+impl TryFrom<SizedCellIdx> for CellIdx {
+    type Error = super::IntoCellIdxError;
+
+    fn try_from(v: SizedCellIdx) -> Result<Self, Self::Error> {
+        Ok((RowIdx::try_from(v.row)?, ColIdx::try_from(v.col)?).into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Shape, Shape16x16, Shape4x4, Shape6x6, Shape9x9, SizedCellIdx};
+    use crate::grid::{ArrGridColMajor, ArrGridRowMajor, CellIdx, Digit, GridMutWithDefault};
+    use itertools::Itertools;
+
+    #[test]
+    fn test_box() {
+        let idx = |row, col| SizedCellIdx { row, col };
+        assert_eq!(0, Shape6x6::box_(idx(1, 2)));
+        assert_eq!(1, Shape6x6::box_(idx(1, 3)));
+        assert_eq!(2, Shape6x6::box_(idx(2, 0)));
+        assert_eq!(5, Shape6x6::box_(idx(5, 5)));
+        CellIdx::iter_row_wise().for_each(|idx| assert_eq!(idx.box_(), Shape9x9::box_(idx.into())));
+    }
+
+    fn assert_units<S: Shape>() {
+        let units = S::iter_units().collect::<Vec<_>>();
+        assert_eq!(3 * S::DIM, units.len());
+        units.iter().for_each(|unit| {
+            assert_eq!(S::DIM, unit.iter().unique().count());
+            assert!(unit.iter().all(|idx| idx.row < S::DIM && idx.col < S::DIM));
+        });
+        (0..S::DIM).for_each(|box_| {
+            assert!(S::iter_box(box_).all(|idx| S::box_(idx) == box_));
+        });
+    }
+
+    #[test]
+    fn test_units() {
+        assert_units::<Shape4x4>();
+        assert_units::<Shape6x6>();
+        assert_units::<Shape9x9>();
+        assert_units::<Shape16x16>();
+    }
+
+    #[test]
+    fn test_index() {
+        let mut grid = ArrGridRowMajor::new();
+        grid[CellIdx::try_of_row_major(10).unwrap()] = Some(Digit::D5);
+        grid[SizedCellIdx { row: 8, col: 8 }] = Some(Digit::D9);
+        assert_eq!(Some(Digit::D5), grid[SizedCellIdx { row: 1, col: 1 }]);
+        assert_eq!(
+            Some(Digit::D9),
+            grid[CellIdx::try_of_row_major(80).unwrap()]
+        );
+        let cols = ArrGridColMajor::copy_of(&grid);
+        assert_eq!(Some(Digit::D5), cols[SizedCellIdx { row: 1, col: 1 }]);
+
+        let mut grid = ArrGridRowMajor::<Shape6x6>::default();
+        grid[SizedCellIdx { row: 1, col: 2 }] = Some(3);
+        assert_eq!(
+            vec![(SizedCellIdx { row: 1, col: 2 }, 3)],
+            grid.iter_set().collect::<Vec<_>>()
+        );
+        assert_eq!(35, grid.iter_unset().count());
+    }
+}
@@ -1,7 +1,11 @@
 pub mod cancellation_flag;
+pub mod constraint;
 pub mod format;
 pub mod grid;
+pub mod generator;
 mod permutator;
+pub mod rater;
 pub mod solver;
 pub mod status;
+pub mod transform;
 mod util;
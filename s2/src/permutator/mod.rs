@@ -56,7 +56,7 @@ where
                     if (self.stack[self.i as usize] as usize) < self.i as usize {
                         self.arr.swap(
                             self.i as usize,
-                            if self.i % 2 == 0 {
+                            if self.i.is_multiple_of(2) {
                                 0u8
                             } else {
                                 self.stack[self.i as usize]
@@ -85,6 +85,9 @@ where
         F: for<'a> FnMut(&'a [Elt]) -> Result<T, E>,
         Cancelled: Fn(&E) -> bool,
     {
+        // A search that stopped early leaves the state machine part way through.
+        self.state = State::Preamble;
+        self.i = 0;
         self.stack.fill(0);
         let len = zip(self.arr.iter_mut(), iter)
             .enumerate()
@@ -176,4 +179,22 @@ mod test {
             all[..]
         );
     }
+
+    #[test]
+    fn reuse() {
+        // The first search stops at the first permutation, the second one must still see all.
+        let mut permutator = Permutator::<5, _>::new();
+        let mut first = None;
+        let _ = permutator.try_find(
+            1..=3,
+            |perm| {
+                first.get_or_insert(perm.to_vec());
+                Ok::<_, ()>(())
+            },
+            |_| false,
+        );
+        assert_eq!(Some(vec![1, 2, 3]), first);
+        let all = all(&mut permutator, 1..=2);
+        assert_eq!([vec![1, 2], vec![2, 1]], all[..]);
+    }
 }
@@ -0,0 +1,281 @@
+use crate::grid::{ArrGridRowMajor, CellIdx, Digit, Grid, DIM};
+use crate::solver::SolverError;
+use itertools::Itertools;
+use std::array;
+use std::sync::LazyLock;
+
+const ALL: u16 = (1u16 << DIM) - 1;
+
+/// Row-major indices of the cells of every row, then every column, then every box.
+static UNITS: LazyLock<[[usize; DIM]; 3 * DIM]> = LazyLock::new(|| {
+    let mut units = [[0; DIM]; 3 * DIM];
+    let mut lens = [0; 3 * DIM];
+    CellIdx::iter_row_wise().for_each(|idx| {
+        let i: usize = idx.row.into();
+        let j: usize = idx.col.into();
+        [i, DIM + j, 2 * DIM + idx.box_()]
+            .into_iter()
+            .for_each(|unit| {
+                units[unit][lens[unit]] = idx.row_major();
+                lens[unit] += 1;
+            })
+    });
+    units
+});
+
+fn rows() -> &'static [[usize; DIM]] {
+    &UNITS[..DIM]
+}
+
+fn cols() -> &'static [[usize; DIM]] {
+    &UNITS[DIM..(2 * DIM)]
+}
+
+fn boxes() -> &'static [[usize; DIM]] {
+    &UNITS[(2 * DIM)..]
+}
+
+fn bit(value: Digit) -> u16 {
+    1u16 << u8::from(value)
+}
+
+fn digits(mask: u16) -> impl Iterator<Item = Digit> {
+    (0..DIM)
+        .filter(move |x| mask & (1u16 << x) != 0)
+        .map(|x| Digit::try_from(x).unwrap())
+}
+
+/// Pencil marks of every cell, as a bit mask per cell where bit `d` stands for digit `d + 1`.
+/// Set cells have no candidates.
+#[derive(Debug, Clone)]
+pub struct Candidates {
+    grid: ArrGridRowMajor,
+    masks: [u16; CellIdx::COUNT],
+}
+
+impl Candidates {
+    pub fn from_grid<T>(grid: &T) -> Result<Self, SolverError>
+    where
+        T: Grid + ?Sized,
+    {
+        let mut t = Self {
+            grid: ArrGridRowMajor::new(),
+            masks: [ALL; CellIdx::COUNT],
+        };
+        for (idx, value) in grid.iter_set() {
+            if t.masks[idx.row_major()] & bit(value) == 0 {
+                return Err(SolverError::ConstraintsViolated);
+            }
+            t.place(idx.row_major(), value);
+        }
+        Ok(t)
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.grid.iter_unset().next().is_none()
+    }
+
+    fn is_set(&self, cell: usize) -> bool {
+        self.grid[CellIdx::try_of_row_major(cell).unwrap()].is_some()
+    }
+
+    fn place(&mut self, cell: usize, value: Digit) {
+        let idx = CellIdx::try_of_row_major(cell).unwrap();
+        self.grid[idx] = Some(value);
+        self.masks[cell] = 0;
+        let i: usize = idx.row.into();
+        let j: usize = idx.col.into();
+        [&rows()[i], &cols()[j], &boxes()[idx.box_()]]
+            .into_iter()
+            .flatten()
+            .for_each(|peer| self.masks[*peer] &= !bit(value));
+    }
+
+    fn eliminate(&mut self, cell: usize, mask: u16) -> bool {
+        let before = self.masks[cell];
+        self.masks[cell] &= !mask;
+        before != self.masks[cell]
+    }
+
+    #[cfg(test)]
+    pub fn set_mask(&mut self, idx: CellIdx, mask: u16) {
+        self.masks[idx.row_major()] = mask;
+    }
+
+    #[cfg(test)]
+    pub fn mask(&self, idx: CellIdx) -> u16 {
+        self.masks[idx.row_major()]
+    }
+
+    /// Fails if an empty cell has no candidates, or a digit has no place left in some unit.
+    pub fn check(&self) -> Result<(), SolverError> {
+        let dead_cell = (0..CellIdx::COUNT).any(|cell| !self.is_set(cell) && self.masks[cell] == 0);
+        let dead_digit = UNITS.iter().any(|unit| {
+            let placed = unit
+                .iter()
+                .filter_map(|cell| self.grid[CellIdx::try_of_row_major(*cell).unwrap()])
+                .fold(0, |acc, value| acc | bit(value));
+            let possible = unit.iter().fold(0, |acc, cell| acc | self.masks[*cell]);
+            placed | possible != ALL
+        });
+        if dead_cell || dead_digit {
+            Err(SolverError::Infeasible)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn naked_single(&mut self) -> bool {
+        match (0..CellIdx::COUNT).find(|cell| self.masks[*cell].count_ones() == 1) {
+            None => false,
+            Some(cell) => {
+                let value = digits(self.masks[cell]).next().unwrap();
+                self.place(cell, value);
+                true
+            }
+        }
+    }
+
+    pub fn hidden_single(&mut self) -> bool {
+        let found = UNITS.iter().find_map(|unit| {
+            digits(ALL).find_map(|value| {
+                unit.iter()
+                    .filter(|cell| self.masks[**cell] & bit(value) != 0)
+                    .exactly_one()
+                    .ok()
+                    .map(|cell| (*cell, value))
+            })
+        });
+        match found {
+            None => false,
+            Some((cell, value)) => {
+                self.place(cell, value);
+                true
+            }
+        }
+    }
+
+    /// Pointing: a digit confined to one line within a box is removed from the rest of the line.
+    /// Claiming: a digit confined to one box within a line is removed from the rest of the box.
+    pub fn locked_candidates(&mut self) -> bool {
+        let lines = || rows().iter().chain(cols().iter());
+        for value in digits(ALL) {
+            for (confining, rest) in boxes()
+                .iter()
+                .cartesian_product(lines())
+                .chain(lines().cartesian_product(boxes().iter()))
+            {
+                let with_value = |cell: &&usize| self.masks[**cell] & bit(value) != 0;
+                let inside = confining.iter().filter(with_value).collect::<Vec<_>>();
+                if inside.len() < 2 || !inside.iter().all(|cell| rest.contains(cell)) {
+                    continue;
+                }
+                let outside = rest
+                    .iter()
+                    .filter(with_value)
+                    .filter(|cell| !confining.contains(cell))
+                    .copied()
+                    .collect::<Vec<_>>();
+                if !outside.is_empty() {
+                    outside.into_iter().for_each(|cell| {
+                        self.eliminate(cell, bit(value));
+                    });
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// `size` empty cells of a unit sharing `size` candidates own them within the unit.
+    pub fn naked_set(&mut self, size: usize) -> bool {
+        for unit in UNITS.iter() {
+            let empty = unit
+                .iter()
+                .copied()
+                .filter(|cell| !self.is_set(*cell))
+                .collect::<Vec<_>>();
+            for set in empty.into_iter().combinations(size) {
+                let mask = set.iter().fold(0, |acc, cell| acc | self.masks[*cell]);
+                if mask.count_ones() as usize != size {
+                    continue;
+                }
+                let progress = unit
+                    .iter()
+                    .filter(|cell| !set.contains(cell))
+                    .filter(|cell| self.eliminate(**cell, mask))
+                    .count()
+                    > 0;
+                if progress {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// `size` digits confined to `size` cells of a unit leave no room for other candidates there.
+    pub fn hidden_set(&mut self, size: usize) -> bool {
+        for unit in UNITS.iter() {
+            let positions: [u16; DIM] = array::from_fn(|d| {
+                unit.iter()
+                    .enumerate()
+                    .filter(|(_, cell)| self.masks[**cell] & (1u16 << d) != 0)
+                    .fold(0, |acc, (k, _)| acc | (1u16 << k))
+            });
+            let free = (0..DIM).filter(|d| positions[*d] != 0);
+            for set in free.combinations(size) {
+                let cells = set.iter().fold(0, |acc, d| acc | positions[*d]);
+                if cells.count_ones() as usize != size {
+                    continue;
+                }
+                let mask = set.iter().fold(0, |acc, d| acc | (1u16 << d));
+                let progress = unit
+                    .iter()
+                    .enumerate()
+                    .filter(|(k, _)| cells & (1u16 << k) != 0)
+                    .filter(|(_, cell)| self.eliminate(**cell, !mask))
+                    .count()
+                    > 0;
+                if progress {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// X-Wing for `size` 2, Swordfish for 3 and Jellyfish for 4, with rows or columns as base.
+    pub fn fish(&mut self, size: usize) -> bool {
+        for value in digits(ALL) {
+            for (base, cover) in [(rows(), cols()), (cols(), rows())] {
+                let positions: [u16; DIM] = array::from_fn(|line| {
+                    base[line]
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, cell)| self.masks[**cell] & bit(value) != 0)
+                        .fold(0, |acc, (k, _)| acc | (1u16 << k))
+                });
+                let candidates = (0..DIM)
+                    .filter(|line| (2..=size).contains(&(positions[*line].count_ones() as usize)));
+                for set in candidates.combinations(size) {
+                    let covered = set.iter().fold(0, |acc, line| acc | positions[*line]);
+                    if covered.count_ones() as usize != size {
+                        continue;
+                    }
+                    let progress = (0..DIM)
+                        .filter(|k| covered & (1u16 << k) != 0)
+                        .flat_map(|k| cover[k].iter().enumerate())
+                        .filter(|(line, _)| !set.contains(line))
+                        .filter(|(_, cell)| self.eliminate(**cell, bit(value)))
+                        .count()
+                        > 0;
+                    if progress {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,244 @@
+use crate::grid::Grid;
+use crate::solver::SolverError;
+use strum::IntoEnumIterator;
+use strum_macros::{Display as DisplayMacros, EnumIter as EnumIterMacro};
+
+mod candidates;
+use candidates::Candidates;
+
+/// Human solving techniques, from the easiest to the hardest. Quads rate between X-Wing and
+/// Swordfish, so a range starting at `XWing` asks for at least a fish or a quad.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, DisplayMacros, EnumIterMacro,
+)]
+pub enum Technique {
+    HiddenSingle,
+    NakedSingle,
+    LockedCandidates,
+    NakedPair,
+    HiddenPair,
+    NakedTriple,
+    HiddenTriple,
+    XWing,
+    NakedQuad,
+    HiddenQuad,
+    Swordfish,
+    Jellyfish,
+    /// None of the above makes progress, the puzzle needs trial and error.
+    Guess,
+}
+
+impl Technique {
+    pub fn is_fish(&self) -> bool {
+        match self {
+            Self::XWing | Self::Swordfish | Self::Jellyfish => true,
+            Self::HiddenSingle
+            | Self::NakedSingle
+            | Self::LockedCandidates
+            | Self::NakedPair
+            | Self::HiddenPair
+            | Self::NakedTriple
+            | Self::HiddenTriple
+            | Self::NakedQuad
+            | Self::HiddenQuad
+            | Self::Guess => false,
+        }
+    }
+
+    fn apply(&self, candidates: &mut Candidates) -> bool {
+        match self {
+            Self::HiddenSingle => candidates.hidden_single(),
+            Self::NakedSingle => candidates.naked_single(),
+            Self::LockedCandidates => candidates.locked_candidates(),
+            Self::NakedPair => candidates.naked_set(2),
+            Self::HiddenPair => candidates.hidden_set(2),
+            Self::NakedTriple => candidates.naked_set(3),
+            Self::HiddenTriple => candidates.hidden_set(3),
+            Self::XWing => candidates.fish(2),
+            Self::NakedQuad => candidates.naked_set(4),
+            Self::HiddenQuad => candidates.hidden_set(4),
+            Self::Swordfish => candidates.fish(3),
+            Self::Jellyfish => candidates.fish(4),
+            Self::Guess => false,
+        }
+    }
+}
+
+/// Rates a puzzle by the hardest technique a human needs to solve it, always applying the easiest
+/// technique that makes progress.
+#[derive(Debug, Default)]
+pub struct Rater;
+
+impl Rater {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns `None` for a grid without empty cells, and `Technique::Guess` when the techniques
+    /// run out before the grid is complete.
+    pub fn rate<T>(&self, grid: &T) -> Result<Option<Technique>, SolverError>
+    where
+        T: Grid + ?Sized,
+    {
+        let mut candidates = Candidates::from_grid(grid)?;
+        let mut hardest = None;
+        while !candidates.is_solved() {
+            candidates.check()?;
+            let technique = Technique::iter()
+                .find(|technique| technique.apply(&mut candidates))
+                .unwrap_or(Technique::Guess);
+            hardest = hardest.max(Some(technique));
+            if technique == Technique::Guess {
+                break;
+            }
+        }
+        Ok(hardest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::candidates::Candidates;
+    use super::{Rater, Technique};
+    use crate::format::{read_from_string, RowMajorAscii};
+    use crate::grid::{ArrGridRowMajor, CellIdx, ColIdx, RowIdx};
+    use crate::solver::SolverError;
+    use strum::IntoEnumIterator;
+
+    fn rate(given: &str) -> Result<Option<Technique>, SolverError> {
+        let given: ArrGridRowMajor =
+            read_from_string(&RowMajorAscii::default(), given.trim()).unwrap();
+        Rater::new().rate(&given)
+    }
+
+    fn idx(i: usize, j: usize) -> CellIdx {
+        (RowIdx::try_from(i).unwrap(), ColIdx::try_from(j).unwrap()).into()
+    }
+
+    #[test]
+    fn test_singles() {
+        let given = r#"
+53__7____
+6__195___
+_98____6_
+8___6___3
+4__8_3__1
+7___2___6
+_6____28_
+___419__5
+____8__79
+"#;
+        assert!(rate(given).unwrap() <= Some(Technique::NakedSingle));
+    }
+
+    #[test]
+    fn test_guess() {
+        let given = r#"
+8________
+__36_____
+_7__9_2__
+_5___7___
+____457__
+___1___3_
+__1____68
+__85___1_
+_9____4__
+"#;
+        assert_eq!(Ok(Some(Technique::Guess)), rate(given));
+    }
+
+    #[test]
+    fn test_complete() {
+        let given = r#"
+534678912
+672195348
+198342567
+859761423
+426853791
+713924856
+961537284
+287419635
+345286179
+"#;
+        assert_eq!(Ok(None), rate(given));
+    }
+
+    #[test]
+    fn test_constraints_violated() {
+        let given = r#"
+55_______
+_________
+_________
+_________
+_________
+_________
+_________
+_________
+_________
+"#;
+        assert_eq!(Err(SolverError::ConstraintsViolated), rate(given));
+    }
+
+    #[test]
+    fn test_x_wing() {
+        let mut candidates = Candidates::from_grid(&ArrGridRowMajor::new()).unwrap();
+        // Digit 1 is confined to columns 1 and 7 in rows 2 and 6.
+        (0..9).filter(|j| *j != 1 && *j != 7).for_each(|j| {
+            candidates.set_mask(idx(2, j), 0b111111110);
+            candidates.set_mask(idx(6, j), 0b111111110);
+        });
+        assert!(candidates.fish(2));
+        (0..9).for_each(|i| {
+            let expected = if i == 2 || i == 6 { 1 } else { 0 };
+            assert_eq!(expected, candidates.mask(idx(i, 1)) & 1);
+            assert_eq!(expected, candidates.mask(idx(i, 7)) & 1);
+        });
+    }
+
+    #[test]
+    fn test_locked_candidates() {
+        let mut candidates = Candidates::from_grid(&ArrGridRowMajor::new()).unwrap();
+        // Digit 1 is confined to row 0 within the top-left box.
+        (1..3)
+            .flat_map(|i| (0..3).map(move |j| (i, j)))
+            .for_each(|(i, j)| candidates.set_mask(idx(i, j), 0b111111110));
+        assert!(candidates.locked_candidates());
+        (3..9).for_each(|j| assert_eq!(0, candidates.mask(idx(0, j)) & 1));
+    }
+
+    #[test]
+    fn test_naked_pair() {
+        let mut candidates = Candidates::from_grid(&ArrGridRowMajor::new()).unwrap();
+        candidates.set_mask(idx(0, 0), 0b11);
+        candidates.set_mask(idx(0, 5), 0b11);
+        assert!(candidates.naked_set(2));
+        (1..9)
+            .filter(|j| *j != 5)
+            .for_each(|j| assert_eq!(0, candidates.mask(idx(0, j)) & 0b11));
+    }
+
+    #[test]
+    fn test_hidden_pair() {
+        let mut candidates = Candidates::from_grid(&ArrGridRowMajor::new()).unwrap();
+        // Digits 1 and 2 are confined to cells 0 and 5 of row 0.
+        (1..9)
+            .filter(|j| *j != 5)
+            .for_each(|j| candidates.set_mask(idx(0, j), 0b111111100));
+        assert!(candidates.hidden_set(2));
+        assert_eq!(0b11, candidates.mask(idx(0, 0)));
+        assert_eq!(0b11, candidates.mask(idx(0, 5)));
+    }
+
+    #[test]
+    fn test_ordering() {
+        let techniques = Technique::iter().collect::<Vec<_>>();
+        assert!(techniques.is_sorted());
+        assert_eq!(
+            vec![Technique::XWing, Technique::Swordfish, Technique::Jellyfish],
+            techniques
+                .into_iter()
+                .filter(Technique::is_fish)
+                .collect::<Vec<_>>()
+        );
+    }
+}
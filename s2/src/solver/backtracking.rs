@@ -0,0 +1,124 @@
+use super::SolverError;
+use crate::cancellation_flag::{CancellationFlag, RateLimitedCancellationFlag};
+use bit_iter::BitIter;
+
+/// Units of a puzzle whose cells are numbered from 0, each holding distinct digits as rows,
+/// columns and boxes do, with the digits each of them uses, one bit per digit.
+#[derive(Debug)]
+pub(super) struct Units {
+    all: u32,
+    /// Cells of each unit.
+    units: Vec<Vec<usize>>,
+    /// Units of each cell.
+    units_of: Vec<Vec<usize>>,
+    used: Vec<u32>,
+}
+
+impl Units {
+    /// `units` of a puzzle with `dim` digits, zero-based, and `values` in its cells.
+    pub fn new(dim: usize, units: Vec<Vec<usize>>, values: &[Option<u8>]) -> Self {
+        let mut units_of = vec![Vec::new(); values.len()];
+        for (i, unit) in units.iter().enumerate() {
+            unit.iter().for_each(|cell| units_of[*cell].push(i));
+        }
+        let mut t = Self {
+            all: u32::MAX >> (u32::BITS as usize - dim),
+            used: vec![0; units.len()],
+            units,
+            units_of,
+        };
+        for (cell, value) in values.iter().enumerate() {
+            if let Some(value) = value {
+                t.set(cell, *value);
+            }
+        }
+        t
+    }
+
+    fn set(&mut self, cell: usize, value: u8) {
+        for unit in &self.units_of[cell] {
+            self.used[*unit] |= 1 << value;
+        }
+    }
+
+    fn unset(&mut self, cell: usize, value: u8) {
+        for unit in &self.units_of[cell] {
+            self.used[*unit] &= !(1 << value);
+        }
+    }
+
+    /// Digits still available at `cell`, one bit per digit.
+    fn domain(&self, cell: usize) -> u32 {
+        !self.units_of[cell]
+            .iter()
+            .fold(0, |acc, unit| acc | self.used[*unit])
+            & self.all
+    }
+}
+
+/// Assignments to branch on: the digits of the most constrained cell, or the only cell left for
+/// a digit in some unit. `None` once every cell is set, and empty at a dead end.
+fn branches(values: &[Option<u8>], units: &Units) -> Option<Vec<(usize, u8)>> {
+    let domains = (0..values.len())
+        .map(|cell| values[cell].map_or(units.domain(cell), |_| 0))
+        .collect::<Vec<_>>();
+    let (cell, domain) = (0..values.len())
+        .filter(|cell| values[*cell].is_none())
+        .map(|cell| (cell, domains[cell]))
+        .min_by_key(|(_, domain)| domain.count_ones())?;
+    if domain.count_ones() > 1 {
+        for (unit, used) in units.units.iter().zip(&units.used) {
+            let (mut once, mut twice) = (0u32, 0u32);
+            for cell in unit.iter().filter(|cell| values[**cell].is_none()) {
+                twice |= once & domains[*cell];
+                once |= domains[*cell];
+            }
+            if units.all & !used & !once != 0 {
+                return Some(Vec::new());
+            }
+            let single = once & !twice & !used;
+            if single != 0 {
+                let value = single.trailing_zeros() as u8;
+                let cell = unit
+                    .iter()
+                    .find(|cell| domains[**cell] & (1 << value) != 0)
+                    .unwrap();
+                return Some(vec![(*cell, value)]);
+            }
+        }
+    }
+    Some(
+        BitIter::from(domain)
+            .map(|value| (cell, value as u8))
+            .collect(),
+    )
+}
+
+/// Backtracking search filling the empty cells of `values` so that every unit holds distinct
+/// digits. Branches on hidden singles first and on the cell with the fewest digits left
+/// otherwise. Whether it found a solution, in which case `values` holds it.
+pub(super) fn solve<const RATE: u64, C>(
+    cancellation_flag: &mut RateLimitedCancellationFlag<'_, RATE, C>,
+    values: &mut [Option<u8>],
+    units: &mut Units,
+) -> Result<bool, SolverError>
+where
+    C: CancellationFlag,
+{
+    if cancellation_flag.cancelled() {
+        return Err(SolverError::Cancelled);
+    }
+    let Some(branches) = branches(values, units) else {
+        return Ok(true);
+    };
+    for (cell, value) in branches {
+        values[cell] = Some(value);
+        units.set(cell, value);
+        if solve(cancellation_flag, values, units)? {
+            return Ok(true);
+        }
+        units.unset(cell, value);
+        values[cell] = None;
+    }
+    Ok(false)
+}
@@ -0,0 +1,207 @@
+use super::backtracking::{self, Units};
+use super::{Solver, SolverError};
+use crate::cancellation_flag::{CancellationFlag, RateLimitedCancellationFlag};
+use crate::grid::{
+    ArrGridRowMajor, CellIdx, CompositeGrid, CompositeLayout, Digit, Grid, GridDiff,
+    GridMutWithDefault, SizedCellIdx, DIM,
+};
+use crate::status::eval_composite_status;
+use std::collections::HashMap;
+
+/// Rows, columns and boxes of every grid of `layout` as indices into `cells`, a shared box
+/// counting once.
+fn units(layout: &CompositeLayout, cells: &[SizedCellIdx]) -> Vec<Vec<usize>> {
+    let position = cells
+        .iter()
+        .enumerate()
+        .map(|(i, idx)| (*idx, i))
+        .collect::<HashMap<_, _>>();
+    let mut units: Vec<Vec<usize>> = Vec::new();
+    for origin in 0..layout.origins().len() {
+        let mut grid_units = vec![Vec::new(); 3 * DIM];
+        for idx in CellIdx::iter_row_wise() {
+            let (i, j): (usize, usize) = (idx.row.into(), idx.col.into());
+            let cell = position[&layout.cell(origin, idx)];
+            grid_units[i].push(cell);
+            grid_units[DIM + j].push(cell);
+            grid_units[2 * DIM + idx.box_()].push(cell);
+        }
+        for unit in grid_units {
+            if !units.contains(&unit) {
+                units.push(unit);
+            }
+        }
+    }
+    units
+}
+
+/// Backtracking search for composite puzzles such as Samurai, solving all grids at once so that
+/// digits placed in a shared box constrain every grid holding it. As a `Solver`, it solves a
+/// classic grid as a composite puzzle of that one grid.
+#[derive(Debug, Default)]
+pub struct CompositeSolver;
+
+impl CompositeSolver {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Same as `Solver::solve` for a composite puzzle: the digits to set to complete `grid`.
+    pub fn solve_composite<C, U>(
+        &self,
+        cancellation_flag: &C,
+        grid: &CompositeGrid,
+    ) -> Result<U, SolverError>
+    where
+        C: CancellationFlag,
+        U: FromIterator<(SizedCellIdx, Digit)>,
+    {
+        if eval_composite_status(grid).is_err() {
+            return Err(SolverError::ConstraintsViolated);
+        }
+        let mut cancellation_flag: RateLimitedCancellationFlag<'_, { 1u64 << 10 }, _> =
+            RateLimitedCancellationFlag::new(cancellation_flag);
+        let cells = grid.layout().iter_cells().collect::<Vec<_>>();
+        let mut values = cells
+            .iter()
+            .map(|idx| grid[*idx].map(u8::from))
+            .collect::<Vec<_>>();
+        let mut units = Units::new(DIM, units(grid.layout(), &cells), &values);
+        if !backtracking::solve(&mut cancellation_flag, &mut values, &mut units)? {
+            return Err(SolverError::Infeasible);
+        }
+        Ok(cells
+            .into_iter()
+            .zip(values)
+            .filter(|(idx, _)| grid[*idx].is_none())
+            .map(|(idx, value)| (idx, Digit::try_from(value.unwrap()).unwrap()))
+            .collect())
+    }
+}
+
+impl Solver for CompositeSolver {
+    fn solve<C, T, U>(&self, cancellation_flag: &C, grid: &T) -> Result<U, SolverError>
+    where
+        C: CancellationFlag,
+        T: Grid + ?Sized,
+        U: FromIterator<GridDiff>,
+    {
+        let layout = CompositeLayout::new(vec![SizedCellIdx::default()]).unwrap();
+        let grid = CompositeGrid::from_grids(layout, &[ArrGridRowMajor::copy_of(grid)]).unwrap();
+        let diff: Vec<_> = self.solve_composite(cancellation_flag, &grid)?;
+        Ok(diff
+            .into_iter()
+            .map(|(idx, value)| GridDiff::Set(idx.try_into().unwrap(), value))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CompositeSolver;
+    use crate::cancellation_flag::NeverCancelled;
+    use crate::format::{read_from_string, RowMajorAscii};
+    use crate::grid::{
+        ArrGridRowMajor, CompositeGrid, CompositeLayout, Digit, Grid, GridMutWithDefault,
+        SizedCellIdx,
+    };
+    use crate::solver::{Solver, SolverError};
+    use crate::status::{eval_composite_status, eval_status, SudokuStatus};
+
+    fn assert_solves(given: &CompositeGrid) -> CompositeGrid {
+        let diff: Vec<_> = CompositeSolver::new()
+            .solve_composite(&NeverCancelled::new(), given)
+            .unwrap();
+        let mut solution = given.clone();
+        diff.into_iter().for_each(|(idx, value)| {
+            assert_eq!(None, given[idx]);
+            solution[idx] = Some(value);
+        });
+        assert_eq!(
+            Ok(SudokuStatus::Complete),
+            eval_composite_status(&solution).map_err(|_| ())
+        );
+        solution
+    }
+
+    #[test]
+    fn test_empty() {
+        [
+            CompositeLayout::samurai(),
+            CompositeLayout::twodoku(),
+            CompositeLayout::butterfly(),
+        ]
+        .into_iter()
+        .for_each(|layout| {
+            assert_solves(&CompositeGrid::new(layout));
+        });
+    }
+
+    #[test]
+    fn test_samurai() {
+        // Keep every third digit of a solution, so the grids only agree through shared boxes.
+        let solution = assert_solves(&CompositeGrid::new(CompositeLayout::samurai()));
+        let mut given = CompositeGrid::new(CompositeLayout::samurai());
+        solution
+            .iter_set()
+            .step_by(3)
+            .for_each(|(idx, value)| given[idx] = Some(value));
+        assert_solves(&given);
+    }
+
+    #[test]
+    fn test_shared_box() {
+        // A 1 in the shared box rules out 1 in the rest of its rows and columns in both grids, and
+        // 2s in its columns in either grid leave no room for a 2 in it.
+        let at = |row, col| SizedCellIdx { row, col };
+        let mut given = CompositeGrid::new(CompositeLayout::twodoku());
+        given[at(6, 6)] = Some(Digit::D1);
+        given[at(6, 14)] = Some(Digit::D1);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            CompositeSolver::new().solve_composite::<_, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+        given[at(6, 14)] = None;
+        given[at(0, 7)] = Some(Digit::D2);
+        given[at(14, 8)] = Some(Digit::D2);
+        given[at(3, 6)] = Some(Digit::D2);
+        assert_eq!(
+            Err(SolverError::Infeasible),
+            CompositeSolver::new().solve_composite::<_, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+    }
+
+    #[test]
+    fn test_solver() {
+        let given: ArrGridRowMajor = read_from_string(
+            &RowMajorAscii::default(),
+            r#"
+53__7____
+6__195___
+_98____6_
+8___6___3
+4__8_3__1
+7___2___6
+_6____28_
+___419__5
+____8__79
+"#
+            .trim(),
+        )
+        .unwrap();
+        let complete = ArrGridRowMajor::with_diff(
+            &given,
+            CompositeSolver::new()
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+                .unwrap()
+                .into_iter(),
+        );
+        assert_eq!(
+            Ok(SudokuStatus::Complete),
+            eval_status(&complete).map_err(|_| ())
+        );
+        assert!(given
+            .iter_set()
+            .all(|(idx, value)| complete[idx] == Some(value)));
+    }
+}
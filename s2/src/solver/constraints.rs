@@ -0,0 +1,179 @@
+use crate::constraint::{Constraint, Rules};
+use crate::grid::{BoxLayout, CellIdx, Digit, Grid};
+use crate::util::{BoolMatrix9x9, Domain};
+
+/// Digits placed in each row, column, box and extra unit, kept up to date as solvers set and
+/// unset digits. The rules of the puzzle other than units narrow domains on top of them.
+#[derive(Debug, Default)]
+pub struct Constraints<'a> {
+    rows: BoolMatrix9x9,
+    cols: BoolMatrix9x9,
+    boxes: BoolMatrix9x9,
+    layout: BoxLayout,
+    /// Drops boxes, leaving a Latin square.
+    latin: bool,
+    /// Digits placed in each unit beyond rows, columns and boxes, one bit per digit.
+    units: Vec<u16>,
+    /// Units beyond rows, columns and boxes of each cell in row-major order, empty without them.
+    units_of: Vec<Vec<usize>>,
+    /// Rules other than units, checked and pruned with at every step.
+    rules: Vec<&'a dyn Constraint>,
+}
+
+impl<'a> Constraints<'a> {
+    /// Rows, columns and boxes of classic sudoku.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Units of `rules`, with the other rules left to `domains`.
+    pub fn with_rules(rules: &'a Rules) -> Self {
+        let (units, others): (Vec<_>, Vec<_>) = rules.extra().partition(|rule| rule.is_unit());
+        let mut t = Self {
+            layout: rules.variants().layout,
+            latin: rules.variants().latin,
+            rules: others,
+            ..Self::new()
+        };
+        if !units.is_empty() {
+            t.units = vec![0; units.len()];
+            t.units_of = vec![Vec::new(); CellIdx::COUNT];
+            for (i, unit) in units.iter().enumerate() {
+                unit.cells()
+                    .iter()
+                    .for_each(|idx| t.units_of[idx.row_major()].push(i));
+            }
+        }
+        t
+    }
+
+    pub fn from_grid<T>(grid: &T, rules: &'a Rules) -> Self
+    where
+        T: Grid + ?Sized,
+    {
+        let mut t = Self::with_rules(rules);
+        grid.iter_set().for_each(|(idx, value)| t.set(idx, value));
+        t
+    }
+
+    fn constraint_indices(&self, idx: CellIdx) -> (u8, u8, u8) {
+        (idx.row.into(), idx.col.into(), self.box_of(idx) as u8)
+    }
+
+    pub fn box_of(&self, idx: CellIdx) -> usize {
+        self.layout.box_of(idx)
+    }
+
+    /// Whether boxes hold distinct digits, that is unless solving a Latin square.
+    pub fn has_boxes(&self) -> bool {
+        !self.latin
+    }
+
+    pub fn unit_count(&self) -> usize {
+        self.units.len()
+    }
+
+    /// Units beyond rows, columns and boxes `idx` belongs to, as indices below `unit_count`.
+    pub fn units_of(&self, idx: CellIdx) -> &[usize] {
+        self.units_of
+            .get(idx.row_major())
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether rules other than units narrow domains, so that `domain` alone falls short of
+    /// `domains`.
+    pub fn has_rules(&self) -> bool {
+        !self.rules.is_empty()
+    }
+
+    pub fn set(&mut self, idx: CellIdx, value: Digit) {
+        let (i, j, box_) = self.constraint_indices(idx);
+        let value: u8 = value.into();
+        self.rows.set((i, value));
+        self.cols.set((j, value));
+        self.boxes.set((box_, value));
+        for unit in self.units_of.get(idx.row_major()).into_iter().flatten() {
+            self.units[*unit] |= 1 << value;
+        }
+    }
+
+    pub fn set_many<I>(&mut self, iter: I)
+    where
+        I: Iterator<Item = (CellIdx, Digit)>,
+    {
+        for (idx, elt) in iter {
+            self.set(idx, elt)
+        }
+    }
+
+    pub fn unset(&mut self, idx: CellIdx, value: Digit) {
+        let (i, j, box_) = self.constraint_indices(idx);
+        let value: u8 = value.into();
+        self.rows.unset((i, value));
+        self.cols.unset((j, value));
+        self.boxes.unset((box_, value));
+        for unit in self.units_of.get(idx.row_major()).into_iter().flatten() {
+            self.units[*unit] &= !(1 << value);
+        }
+    }
+
+    pub fn unset_many<I>(&mut self, iter: I)
+    where
+        I: Iterator<Item = (CellIdx, Digit)>,
+    {
+        for (idx, elt) in iter {
+            self.unset(idx, elt)
+        }
+    }
+
+    /// Digits no unit of `idx` holds yet.
+    pub fn domain(&self, idx: CellIdx) -> Domain {
+        let (i, j, box_) = self.constraint_indices(idx);
+        let blocked = self.rows.row(i) | self.cols.row(j);
+        let blocked = match self.latin {
+            true => blocked,
+            false => blocked | self.boxes.row(box_),
+        };
+        self.units_of(idx)
+            .iter()
+            .fold(blocked, |acc, unit| acc | self.units[*unit].into())
+            .into()
+    }
+
+    /// Domains of every cell of `grid` in row-major order, set cells keeping their digit only.
+    /// Units rule out the digits placed in them, then the other rules narrow the domains in turn
+    /// until none of them rules out anything more. `None` if the digits set in `grid` break one
+    /// of these rules.
+    pub fn domains<G>(&self, grid: &G) -> Option<[Domain; CellIdx::COUNT]>
+    where
+        G: Grid,
+    {
+        let domains = std::array::from_fn(|i| {
+            let idx = CellIdx::try_of_row_major(i).unwrap();
+            match grid[idx] {
+                Some(value) => Domain::from_mask(1 << u8::from(value)),
+                None => self.domain(idx),
+            }
+        });
+        if self.rules.is_empty() {
+            return Some(domains);
+        }
+        if self.rules.iter().any(|rule| !rule.validate(grid)) {
+            return None;
+        }
+        let mut masks = domains.map(|domain| domain.mask());
+        let mut changed = true;
+        while changed {
+            let before = masks;
+            self.rules.iter().for_each(|rule| rule.prune(&mut masks));
+            changed = masks != before;
+        }
+        if grid
+            .iter_set()
+            .any(|(idx, value)| masks[idx.row_major()] & (1 << u8::from(value)) == 0)
+        {
+            return None;
+        }
+        Some(masks.map(Domain::from_mask))
+    }
+}
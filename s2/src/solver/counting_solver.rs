@@ -0,0 +1,249 @@
+use super::{Constraints, SolverError};
+use crate::cancellation_flag::{CancellationFlag, RateLimitedCancellationFlag};
+use crate::constraint::{Constraint, Rules, Variants};
+use crate::grid::{ArrGridRowMajor, CellIdx, Grid, GridMutWithDefault};
+use crate::status::eval_variants_status;
+use crate::util::Domain;
+
+/// Exhaustive backtracking search that counts solutions instead of returning the first one.
+#[derive(Debug, Default)]
+pub struct CountingSolver {
+    rules: Rules,
+}
+
+impl CountingSolver {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Only counts solutions where `constraints` hold on top of rows, columns and boxes.
+    pub fn with_constraints(mut self, constraints: Vec<Box<dyn Constraint>>) -> Self {
+        self.rules.variants_mut().constraints = constraints;
+        self
+    }
+
+    /// Counts solutions for every rule of `variants`, replacing the ones given so far.
+    pub fn with_variants(mut self, variants: Variants) -> Self {
+        self.rules = variants.into();
+        self
+    }
+
+    /// Rules the solver counts solutions for, as `status::eval_variants_status` takes them. They
+    /// are built on the first count and reused by the next ones.
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Counts solutions of `grid`, stopping as soon as `limit` of them are found.
+    pub fn count<C, T>(
+        &self,
+        cancellation_flag: &C,
+        grid: &T,
+        limit: usize,
+    ) -> Result<usize, SolverError>
+    where
+        C: CancellationFlag,
+        T: Grid + ?Sized,
+    {
+        let mut grid = ArrGridRowMajor::copy_of(grid);
+        if eval_variants_status(&grid, &self.rules).is_err() {
+            return Err(SolverError::ConstraintsViolated);
+        }
+        let mut cancellation_flag: RateLimitedCancellationFlag<'_, { 1u64 << 10 }, _> =
+            RateLimitedCancellationFlag::new(cancellation_flag);
+        let mut constraints = Constraints::from_grid(&grid, &self.rules);
+        if constraints.domains(&grid).is_none() {
+            return Err(SolverError::ConstraintsViolated);
+        }
+        let mut found = 0;
+        count(
+            &mut cancellation_flag,
+            &mut grid,
+            &mut constraints,
+            limit,
+            &mut found,
+        )?;
+        Ok(found)
+    }
+
+    pub fn is_unique<C, T>(&self, cancellation_flag: &C, grid: &T) -> Result<bool, SolverError>
+    where
+        C: CancellationFlag,
+        T: Grid + ?Sized,
+    {
+        Ok(self.count(cancellation_flag, grid, 2)? == 1)
+    }
+}
+
+fn most_constrained(
+    grid: &ArrGridRowMajor,
+    constraints: &Constraints<'_>,
+) -> Option<(CellIdx, Domain)> {
+    let mut best: Option<(CellIdx, Domain)> = None;
+    for idx in grid.iter_unset() {
+        let domain = constraints.domain(idx);
+        if best.is_none_or(|(_, best)| domain.size() < best.size()) {
+            best = Some((idx, domain));
+            if domain.size() <= 1 {
+                break;
+            }
+        }
+    }
+    best
+}
+
+fn count<const RATE: u64, C>(
+    cancellation_flag: &mut RateLimitedCancellationFlag<'_, RATE, C>,
+    grid: &mut ArrGridRowMajor,
+    constraints: &mut Constraints<'_>,
+    limit: usize,
+    found: &mut usize,
+) -> Result<(), SolverError>
+where
+    C: CancellationFlag,
+{
+    if cancellation_flag.cancelled() {
+        return Err(SolverError::Cancelled);
+    }
+    let next = if !constraints.has_rules() {
+        most_constrained(grid, constraints)
+    } else {
+        let Some(domains) = constraints.domains(grid) else {
+            return Ok(());
+        };
+        grid.iter_unset()
+            .map(|idx| (idx, domains[idx.row_major()]))
+            .min_by_key(|(_, domain)| domain.size())
+    };
+    let Some((idx, domain)) = next else {
+        *found += 1;
+        return Ok(());
+    };
+    for value in domain.iter() {
+        grid[idx] = Some(value);
+        constraints.set(idx, value);
+        let res = count(cancellation_flag, grid, constraints, limit, found);
+        constraints.unset(idx, value);
+        grid[idx] = None;
+        res?;
+        if *found >= limit {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::CountingSolver;
+    use crate::cancellation_flag::{AlreadyCancelled, NeverCancelled};
+    use crate::constraint::{classic, Distinct, Variants};
+    use crate::format::{read_from_string, RowMajorAscii};
+    use crate::grid::{ArrGridRowMajor, CellIdx, Digit};
+    use crate::solver::SolverError;
+
+    fn feasible() -> ArrGridRowMajor {
+        let given = r#"
+53__7____
+6__195___
+_98____6_
+8___6___3
+4__8_3__1
+7___2___6
+_6____28_
+___419__5
+____8__79
+"#
+        .trim();
+        read_from_string(&RowMajorAscii::default(), given).unwrap()
+    }
+
+    #[test]
+    fn test_unique() {
+        let solver = CountingSolver::new();
+        assert_eq!(Ok(1), solver.count(&NeverCancelled::new(), &feasible(), 10));
+        assert_eq!(
+            Ok(true),
+            solver.is_unique(&NeverCancelled::new(), &feasible())
+        );
+    }
+
+    #[test]
+    fn test_limit() {
+        let solver = CountingSolver::new();
+        let given = ArrGridRowMajor::new();
+        assert_eq!(Ok(3), solver.count(&NeverCancelled::new(), &given, 3));
+        assert_eq!(Ok(false), solver.is_unique(&NeverCancelled::new(), &given));
+    }
+
+    #[test]
+    fn test_constraints() {
+        let idx = |x| CellIdx::try_of_row_major(x).unwrap();
+        assert_eq!(
+            Ok(1),
+            CountingSolver::new().with_constraints(classic()).count(
+                &NeverCancelled::new(),
+                &feasible(),
+                10
+            )
+        );
+        // Both cells hold a 5.
+        let distinct = Distinct::new(vec![idx(0), idx(14)]);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            CountingSolver::new()
+                .with_constraints(vec![Box::new(distinct)])
+                .count(&NeverCancelled::new(), &feasible(), 10)
+        );
+    }
+
+    #[test]
+    fn test_variants() {
+        // Cyclic shifts by one make a Latin square, but repeat digits in every box.
+        let mut grid = ArrGridRowMajor::new();
+        for idx in CellIdx::iter_row_wise() {
+            let (i, j): (usize, usize) = (idx.row.into(), idx.col.into());
+            grid[idx] = Some(Digit::try_from(((i + j) % 9) as u8).unwrap());
+        }
+        grid[CellIdx::try_of_row_major(40).unwrap()] = None;
+        let latin = CountingSolver::new().with_variants(Variants {
+            latin: true,
+            ..Default::default()
+        });
+        assert_eq!(Ok(1), latin.count(&NeverCancelled::new(), &grid, 10));
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            CountingSolver::new().count(&NeverCancelled::new(), &grid, 10)
+        );
+    }
+
+    #[test]
+    fn test_constraints_violated() {
+        let given = r#"
+55_______
+_________
+_________
+_________
+_________
+_________
+_________
+_________
+_________
+"#
+        .trim();
+        let given: ArrGridRowMajor = read_from_string(&RowMajorAscii::default(), given).unwrap();
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            CountingSolver::new().count(&NeverCancelled::new(), &given, 2)
+        );
+    }
+
+    #[test]
+    fn test_cancelled() {
+        let given = ArrGridRowMajor::new();
+        assert_eq!(
+            Err(SolverError::Cancelled),
+            CountingSolver::new().count(&AlreadyCancelled::new(), &given, usize::MAX)
+        );
+    }
+}
@@ -1,11 +1,18 @@
-use super::{HiddenSets, Solver, SolverError};
-use crate::cancellation_flag::CancellationFlag;
+use super::backtracking::{self, Units};
+use super::{Constraints, HiddenSets, Solver, SolverError};
+use crate::cancellation_flag::{CancellationFlag, RateLimitedCancellationFlag};
+use crate::constraint::{Constraint, Rules, Variants};
 use crate::grid;
-use crate::grid::{ArrGridRowMajor, CellIdx, Digit, Grid, GridDiff, GridMut, GridMutWithDefault};
+use crate::grid::{
+    ArrGridRowMajor, BoxLayout, Cage, CellIdx, ChessMove, Digit, Grid, GridDiff, GridMut,
+    GridMutWithDefault, Line, OutsideClue, Pair, Parity, Region, Shape, Shape9x9, SizedCellIdx,
+};
 use crate::permutator::Permutator;
-use crate::util::{BoolMatrix9x9, Domain, SliceGroupByIterator};
+use crate::status::eval_sized_status;
+use crate::util::{Domain, SliceGroupByIterator};
 use std::array;
 use std::iter::{empty, once, zip};
+use std::marker::PhantomData;
 use strum::EnumCount;
 
 // TODO(kostya): delete
@@ -14,71 +21,6 @@ const DEBUG_ITER_STATE_EACH: bool = false;
 const DEBUG_RECURSION_DEPTH: bool = false;
 const DEBUG_TOTAL_ITER_COUNT: bool = true;
 
-#[derive(Debug, Default)]
-struct Constraints {
-    rows: BoolMatrix9x9,
-    cols: BoolMatrix9x9,
-    boxes: BoolMatrix9x9,
-}
-
-impl Constraints {
-    fn new() -> Self {
-        Default::default()
-    }
-
-    fn from_grid<T>(grid: &T) -> Self
-    where
-        T: Grid + ?Sized,
-    {
-        let mut t = Self::new();
-        grid.iter_set().for_each(|(idx, value)| t.set(idx, value));
-        t
-    }
-
-    fn constraint_indices(idx: CellIdx) -> (u8, u8, u8) {
-        (idx.row.into(), idx.col.into(), idx.box_() as u8)
-    }
-
-    fn set(&mut self, idx: CellIdx, value: Digit) {
-        let (i, j, box_) = Self::constraint_indices(idx);
-        let value: u8 = value.into();
-        self.rows.set((i, value));
-        self.cols.set((j, value));
-        self.boxes.set((box_, value));
-    }
-
-    fn set_many<I>(&mut self, iter: I)
-    where
-        I: Iterator<Item = (CellIdx, Digit)>,
-    {
-        for (idx, elt) in iter {
-            self.set(idx, elt)
-        }
-    }
-
-    fn unset(&mut self, idx: CellIdx, value: Digit) {
-        let (i, j, box_) = Self::constraint_indices(idx);
-        let value: u8 = value.into();
-        self.rows.unset((i, value));
-        self.cols.unset((j, value));
-        self.boxes.unset((box_, value));
-    }
-
-    fn unset_many<I>(&mut self, iter: I)
-    where
-        I: Iterator<Item = (CellIdx, Digit)>,
-    {
-        for (idx, elt) in iter {
-            self.unset(idx, elt)
-        }
-    }
-
-    fn domain(&self, idx: CellIdx) -> Domain {
-        let (i, j, box_) = Self::constraint_indices(idx);
-        (self.rows.row(i) | self.cols.row(j) | self.boxes.row(box_)).into()
-    }
-}
-
 #[derive(Debug)]
 struct EmptyCellsByDomainSize {
     len: [u8; Digit::COUNT + 1],
@@ -126,6 +68,8 @@ struct GroupedByUnit {
     cols: [[(Domain, CellIdx); grid::DIM]; grid::DIM],
     boxes_lens: [u8; grid::DIM],
     boxes: [[(Domain, CellIdx); grid::DIM]; grid::DIM],
+    /// Units beyond rows, columns and boxes, as extra regions and custom units are.
+    units: Vec<Vec<(Domain, CellIdx)>>,
 }
 
 impl GroupedByUnit {
@@ -133,43 +77,42 @@ impl GroupedByUnit {
         self.rows_lens.fill(0);
         self.cols_lens.fill(0);
         self.boxes_lens.fill(0);
+        self.units.iter_mut().for_each(Vec::clear);
     }
 
-    fn init<I>(&mut self, iter: I)
+    fn init<I>(&mut self, iter: I, constraints: &Constraints<'_>)
     where
         I: Iterator<Item = (CellIdx, Domain)>,
     {
         self.clear();
+        self.units.resize_with(constraints.unit_count(), Vec::new);
         iter.for_each(|(idx, domain)| {
             let row: usize = idx.row.into();
             let col: usize = idx.col.into();
-            let box_: usize = idx.box_();
+            let box_: usize = constraints.box_of(idx);
             self.rows[row][self.rows_lens[row] as usize] = (domain, idx);
             self.cols[col][self.cols_lens[col] as usize] = (domain, idx);
-            self.boxes[box_][self.boxes_lens[box_] as usize] = (domain, idx);
             self.rows_lens[row] += 1;
             self.cols_lens[col] += 1;
-            self.boxes_lens[box_] += 1;
+            // Boxes left empty are skipped like complete ones.
+            if constraints.has_boxes() {
+                self.boxes[box_][self.boxes_lens[box_] as usize] = (domain, idx);
+                self.boxes_lens[box_] += 1;
+            }
+            for unit in constraints.units_of(idx) {
+                self.units[*unit].push((domain, idx));
+            }
         });
         // TODO(kostya): random shuffle within the set that has the same domain within unit.
         zip(self.rows_lens.iter(), self.rows.iter_mut())
             .chain(zip(self.cols_lens.iter(), self.cols.iter_mut()))
             .chain(zip(self.boxes_lens.iter(), self.boxes.iter_mut()))
             .filter(|(len, _)| **len > 1)
-            .for_each(|(len, unit)| unit[..(*len as usize)].sort_unstable())
-    }
-
-    fn iter_equal_domains(&self) -> impl Iterator<Item = &[(Domain, CellIdx)]> {
-        zip(self.rows_lens.iter(), self.rows.iter())
-            .chain(zip(self.cols_lens.iter(), self.cols.iter()))
-            .chain(zip(self.boxes_lens.iter(), self.boxes.iter()))
-            .filter(|(len, _)| **len > 0)
-            .flat_map(|(len, unit)| {
-                SliceGroupByIterator::<(Domain, CellIdx), _>::new(
-                    &unit[..(*len as usize)],
-                    |lhs, rhs| lhs.0 == rhs.0,
-                )
-            })
+            .for_each(|(len, unit)| unit[..(*len as usize)].sort_unstable());
+        self.units
+            .iter_mut()
+            .filter(|unit| unit.len() > 1)
+            .for_each(|unit| unit.sort_unstable())
     }
 
     fn iter_units(&self) -> impl Iterator<Item = &[(Domain, CellIdx)]> {
@@ -178,6 +121,18 @@ impl GroupedByUnit {
             .chain(zip(self.boxes_lens.iter(), self.boxes.iter()))
             .filter(|(len, _)| **len > 0)
             .map(|(len, unit)| &unit[..(*len as usize)])
+            .chain(
+                self.units
+                    .iter()
+                    .filter(|unit| !unit.is_empty())
+                    .map(Vec::as_slice),
+            )
+    }
+
+    fn iter_equal_domains(&self) -> impl Iterator<Item = &[(Domain, CellIdx)]> {
+        self.iter_units().flat_map(|unit| {
+            SliceGroupByIterator::<(Domain, CellIdx), _>::new(unit, |lhs, rhs| lhs.0 == rhs.0)
+        })
     }
 }
 
@@ -187,6 +142,7 @@ struct StackFrame {
     empty_cells: EmptyCellsByDomainSize,
     grouped_by_unit: GroupedByUnit,
     permutator: Permutator<5, Digit>,
+    #[allow(dead_code)]
     hidden_sets: HiddenSets<CellIdx>,
 }
 
@@ -287,13 +243,13 @@ impl<'caller> DiffTail<'caller> {
         &mut self,
         iter: I,
         grid: &mut G,
-        constraints: &mut Constraints,
+        constraints: &mut Constraints<'_>,
         f: F,
     ) -> Result<usize, SolverError>
     where
         I: Iterator<Item = (CellIdx, Digit)>,
         G: GridMut,
-        F: FnOnce(&mut G, &mut Constraints, &mut DiffTail<'_>) -> Result<usize, SolverError>,
+        F: FnOnce(&mut G, &mut Constraints<'_>, &mut DiffTail<'_>) -> Result<usize, SolverError>,
     {
         let cnt = self.push(iter);
         let (head, tail) = self.0.split_at_mut(cnt);
@@ -311,56 +267,22 @@ impl<'caller> DiffTail<'caller> {
     }
 }
 
-#[derive(Debug)]
-struct RateLimitedCancellationFlag<'a, const RATE: u64, C>
-where
-    C: CancellationFlag,
-{
-    count: u64,
-    cancellation_flag: &'a C,
-}
-
-impl<'a, const RATE: u64, C> RateLimitedCancellationFlag<'a, RATE, C>
-where
-    C: CancellationFlag,
-{
-    fn new(cancellation_flag: &'a C) -> Self {
-        Self {
-            count: 0,
-            cancellation_flag,
-        }
-    }
-
-    fn cancelled(&mut self) -> bool {
-        self.count += 1;
-        self.count % RATE == 0 && self.cancellation_flag.cancelled()
-    }
-
-    fn count(&self) -> u64 {
-        self.count
-    }
-
-    fn never_checked(&self) -> bool {
-        self.count() == 0
-    }
-}
-
 #[derive(Debug, Default)]
-struct State {
+struct State<'a> {
     stack: Stack,
     diff: Diff,
     grid: ArrGridRowMajor,
-    constraints: Constraints,
+    constraints: Constraints<'a>,
 }
 
-impl State {
-    fn from_grid<T>(grid: &T) -> Self
+impl<'a> State<'a> {
+    fn from_grid<T>(grid: &T, rules: &'a Rules) -> Self
     where
         T: Grid + ?Sized,
     {
         Self {
             grid: ArrGridRowMajor::copy_of(grid),
-            constraints: Constraints::from_grid(grid),
+            constraints: Constraints::from_grid(grid, rules),
             ..Default::default()
         }
     }
@@ -370,7 +292,7 @@ fn solve_inner<const RATE: u64, I, C, G>(
     diff: I,
     cancellation_flag: &mut RateLimitedCancellationFlag<'_, RATE, C>,
     grid: &mut G,
-    constraints: &mut Constraints,
+    constraints: &mut Constraints<'_>,
     stack: &mut StackTail<'_>,
     diff_tail: &mut DiffTail<'_>,
 ) -> Result<usize, SolverError>
@@ -388,7 +310,7 @@ fn solve<const RATE: u64, C, G>(
     cancellation_flag: &mut RateLimitedCancellationFlag<'_, RATE, C>,
     frame: &mut StackFrame,
     grid: &mut G,
-    constraints: &mut Constraints,
+    constraints: &mut Constraints<'_>,
     stack: &mut StackTail<'_>,
     diff: &mut DiffTail<'_>,
 ) -> Result<usize, SolverError>
@@ -400,7 +322,7 @@ where
         frame.count += 1;
     }
 
-    if DEBUG_ITER_STATE && (DEBUG_ITER_STATE_EACH || cancellation_flag.count() % (1u64 << 14) == 0)
+    if DEBUG_ITER_STATE && (DEBUG_ITER_STATE_EACH || cancellation_flag.count().is_multiple_of(1u64 << 14))
     {
         println!("=====DEBUG===== step={}", cancellation_flag.count());
         println!(
@@ -410,9 +332,14 @@ where
         println!("=====DEBUG=====");
     }
 
-    frame
-        .grouped_by_unit
-        .init(grid.iter_unset().map(|idx| (idx, constraints.domain(idx))));
+    let Some(domains) = constraints.domains(grid) else {
+        return Err(SolverError::Infeasible);
+    };
+
+    frame.grouped_by_unit.init(
+        grid.iter_unset().map(|idx| (idx, domains[idx.row_major()])),
+        constraints,
+    );
 
     match frame
         .grouped_by_unit
@@ -512,40 +439,122 @@ where
 
     frame.empty_cells.init(
         grid.iter_unset()
-            .map(|idx| (idx, constraints.domain(idx).size())),
+            .map(|idx| (idx, domains[idx.row_major()].size())),
     );
 
-    frame
-        .empty_cells
+    // Every solution sets the most constrained cell, so if none of its digits works, no other
+    // cell is worth trying.
+    let idx = *frame.empty_cells.iter().next().unwrap();
+    domains[idx.row_major()]
         .iter()
-        .map(|idx| {
-            constraints
-                .domain(*idx)
-                .iter()
-                .map(|value| {
-                    solve_inner(
-                        once((*idx, value)),
-                        cancellation_flag,
-                        grid,
-                        constraints,
-                        stack,
-                        diff,
-                    )
-                })
-                .find_map(SolverError::ok_or_cancelled)
-                .ok_or(SolverError::Infeasible)?
+        .map(|value| {
+            solve_inner(
+                once((idx, value)),
+                cancellation_flag,
+                grid,
+                constraints,
+                stack,
+                diff,
+            )
         })
         .find_map(SolverError::ok_or_cancelled)
         .ok_or(SolverError::Infeasible)?
 }
 
-#[derive(Debug, Default)]
-pub struct GreedySolver;
+/// Solves grids of shape `S`. Classic 9x9 grids are solved greedily, narrowing domains and
+/// filling hidden singles first, under the rules set by the builders. Other shapes are solved by
+/// backtracking with rows, columns and boxes as the only rules.
+#[derive(Debug)]
+pub struct GreedySolver<S: Shape = Shape9x9> {
+    rules: Rules,
+    shape: PhantomData<S>,
+}
+
+impl<S: Shape> Default for GreedySolver<S> {
+    fn default() -> Self {
+        Self {
+            rules: Default::default(),
+            shape: PhantomData,
+        }
+    }
+}
 
 impl GreedySolver {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Solves jigsaw sudoku: boxes of `layout` replace the 3x3 ones.
+    pub fn with_layout(mut self, layout: BoxLayout) -> Self {
+        self.rules.variants_mut().layout = layout;
+        self
+    }
+
+    /// Solves killer sudoku: digits of each cage are distinct and add up to its sum.
+    pub fn with_cages(mut self, cages: Vec<Cage>) -> Self {
+        self.rules.variants_mut().cages = cages;
+        self
+    }
+
+    /// Requires every digit once in each extra region, as rows, columns and boxes do.
+    pub fn with_regions(mut self, regions: Vec<Region>) -> Self {
+        self.rules.variants_mut().regions = regions;
+        self
+    }
+
+    /// Forbids equal digits a move of any of `moves` apart, as in anti-knight sudoku.
+    pub fn with_chess_moves(mut self, moves: Vec<ChessMove>) -> Self {
+        self.rules.variants_mut().moves = moves;
+        self
+    }
+
+    /// Requires thermometers, arrows and palindromes to hold.
+    pub fn with_lines(mut self, lines: Vec<Line>) -> Self {
+        self.rules.variants_mut().lines = lines;
+        self
+    }
+
+    /// Requires the digits of each pair to satisfy its relation, as in Kropki or XV sudoku.
+    pub fn with_pairs(mut self, pairs: Vec<Pair>) -> Self {
+        self.rules.variants_mut().pairs = pairs;
+        self
+    }
+
+    /// Requires rows and columns to satisfy sandwich, skyscraper and X-sum clues.
+    pub fn with_clues(mut self, clues: Vec<OutsideClue>) -> Self {
+        self.rules.variants_mut().clues = clues;
+        self
+    }
+
+    /// Solves a Latin square: rows and columns hold every digit once, boxes are left out.
+    pub fn without_boxes(mut self) -> Self {
+        self.rules.variants_mut().latin = true;
+        self
+    }
+
+    /// Restricts shaded cells to odd or to even digits.
+    pub fn with_parities(mut self, parities: Vec<(CellIdx, Parity)>) -> Self {
+        self.rules.variants_mut().parities = parities;
+        self
+    }
+
+    /// Requires `constraints` to hold on top of the rules above.
+    pub fn with_constraints(mut self, constraints: Vec<Box<dyn Constraint>>) -> Self {
+        self.rules.variants_mut().constraints = constraints;
+        self
+    }
+
+    /// Solves for every rule of `variants`, replacing the ones given so far.
+    pub fn with_variants(mut self, variants: Variants) -> Self {
+        self.rules = variants.into();
+        self
+    }
+
+    /// Rules the solver solves for, as `status::eval_variants_status` takes them. They are built
+    /// on the first solve and reused by the next ones.
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
 }
 
 impl Solver for GreedySolver {
@@ -555,9 +564,12 @@ impl Solver for GreedySolver {
         T: Grid + ?Sized,
         U: FromIterator<GridDiff>,
     {
+        let mut mem = Box::new(State::from_grid(grid, &self.rules));
+        if !self.rules.validate(&mem.grid) {
+            return Err(SolverError::ConstraintsViolated);
+        }
         let mut cancellation_flag: RateLimitedCancellationFlag<'_, { 1u64 << 10 }, _> =
             RateLimitedCancellationFlag::new(cancellation_flag);
-        let mut mem = Box::new(State::from_grid(grid));
         let len = StackTail::from(&mut mem.stack)
             .with(|frame, stack| {
                 DiffTail::from(&mut mem.diff).with(
@@ -601,13 +613,53 @@ impl Solver for GreedySolver {
     }
 }
 
+impl<S: Shape<Digit = u8>> GreedySolver<S> {
+    /// Same as `Solver::solve` for a grid of any shape but 9x9: the digits to set to complete
+    /// `grid`.
+    pub fn solve_sized<C, U>(
+        &self,
+        cancellation_flag: &C,
+        grid: &ArrGridRowMajor<S>,
+    ) -> Result<U, SolverError>
+    where
+        C: CancellationFlag,
+        U: FromIterator<(SizedCellIdx, u8)>,
+    {
+        if eval_sized_status(grid).is_err() {
+            return Err(SolverError::ConstraintsViolated);
+        }
+        let mut cancellation_flag: RateLimitedCancellationFlag<'_, { 1u64 << 10 }, _> =
+            RateLimitedCancellationFlag::new(cancellation_flag);
+        let mut values = S::iter_row_wise().map(|idx| grid[idx]).collect::<Vec<_>>();
+        let units = S::iter_units()
+            .map(|unit| unit.iter().map(|idx| idx.row_major::<S>()).collect())
+            .collect();
+        let mut units = Units::new(S::DIM, units, &values);
+        if !backtracking::solve(&mut cancellation_flag, &mut values, &mut units)? {
+            return Err(SolverError::Infeasible);
+        }
+        Ok(S::iter_row_wise()
+            .zip(values)
+            .filter(|(idx, _)| grid[*idx].is_none())
+            .filter_map(|(idx, value)| Some((idx, value?)))
+            .collect())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::{GreedySolver, Solver, SolverError};
     use crate::cancellation_flag::{Atomic, NeverCancelled};
-    use crate::format::{read_from_string, write_string, RowMajorAscii};
-    use crate::grid::{ArrGridRowMajor, Grid, GridMutWithDefault};
-    use crate::status::{eval_status, SudokuStatus};
+    use crate::constraint::Constraint;
+    use crate::format::{read_from_string, write_string, BoxLayoutAscii, RowMajorAscii};
+    use crate::grid::{
+        ArrGridRowMajor, Cage, CellIdx, ChessMove, ClueKind, ColIdx, Digit, Grid,
+        GridMutWithDefault, Line, OutsideClue, Pair, Parity, Region, RowIdx, Shape, Shape16x16,
+        Shape25x25, Shape4x4, Shape6x6, Side,
+    };
+    use crate::status::{eval_sized_status, eval_status, eval_variants_status, SudokuStatus};
+    use itertools::Itertools;
+    use std::ops::Index;
     use std::sync::Arc;
     use std::thread;
     use std::time::Duration;
@@ -702,6 +754,472 @@ ____8__79
         assert_eq!(&expected, &complete);
     }
 
+    #[test]
+    fn test_killer() {
+        let solution = r#"
+534678912
+672195348
+198342567
+859761423
+426853791
+713924856
+961537284
+287419635
+345286179
+"#
+        .trim();
+        let solution: ArrGridRowMajor =
+            read_from_string(&RowMajorAscii::default(), solution).unwrap();
+        // Vertical dominoes covering rows in pairs, and the last row split in three.
+        let cage = |cells: Vec<CellIdx>| {
+            let sum = cells
+                .iter()
+                .map(|idx| u8::from(solution[*idx].unwrap()) + 1)
+                .sum();
+            Cage::new(cells, sum).unwrap()
+        };
+        let idx = |i: usize, j: usize| CellIdx::try_of_row_major(i * 9 + j).unwrap();
+        let cages = (0..4)
+            .flat_map(|i| (0..9).map(move |j| vec![idx(2 * i, j), idx(2 * i + 1, j)]))
+            .chain((0..3).map(|k| (0..3).map(|j| idx(8, 3 * k + j)).collect()))
+            .map(cage)
+            .collect::<Vec<_>>();
+        let solver = GreedySolver::new().with_cages(cages.clone());
+        let given = ArrGridRowMajor::new();
+        let complete = ArrGridRowMajor::with_diff(
+            &given,
+            solver
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+                .unwrap()
+                .into_iter(),
+        );
+        assert_eq!(
+            SudokuStatus::Complete,
+            eval_variants_status(&complete, solver.rules()).unwrap()
+        );
+
+        let mut given = ArrGridRowMajor::new();
+        given[idx(0, 0)] = Some(Digit::D9);
+        given[idx(1, 0)] = Some(Digit::D8);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            solver.solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+    }
+
+    #[test]
+    fn test_jigsaw() {
+        let layout = BoxLayoutAscii::default()
+            .read_from_string(
+                r#"
+AAABBBCCC
+AAAABBCCC
+AADBBBCCC
+DDDEBEFFF
+DDEEEEFFF
+DDDEEEFFF
+GGGHHHIII
+GGGHHHIII
+GGGHHHIII
+"#,
+            )
+            .unwrap();
+        let solver = GreedySolver::new().with_layout(layout);
+        let given = ArrGridRowMajor::new();
+        let complete = ArrGridRowMajor::with_diff(
+            &given,
+            solver
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+                .unwrap()
+                .into_iter(),
+        );
+        assert_eq!(
+            SudokuStatus::Complete,
+            eval_variants_status(&complete, solver.rules()).unwrap()
+        );
+
+        // Both 1s are in the first jigsaw box.
+        let mut given = ArrGridRowMajor::new();
+        given[CellIdx::try_of_row_major(12).unwrap()] = Some(Digit::D1);
+        given[CellIdx::try_of_row_major(18).unwrap()] = Some(Digit::D1);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            solver.solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+    }
+
+    #[test]
+    fn test_chess() {
+        let given = ArrGridRowMajor::new();
+        [[ChessMove::Knight], [ChessMove::King]]
+            .into_iter()
+            .for_each(|moves| {
+                let solver = GreedySolver::new().with_chess_moves(moves.to_vec());
+                let complete = ArrGridRowMajor::with_diff(
+                    &given,
+                    solver
+                        .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+                        .unwrap()
+                        .into_iter(),
+                );
+                assert_eq!(
+                    SudokuStatus::Complete,
+                    eval_variants_status(&complete, solver.rules()).unwrap()
+                );
+            });
+
+        // Both 1s are a knight's move apart.
+        let mut given = ArrGridRowMajor::new();
+        given[CellIdx::try_of_row_major(0).unwrap()] = Some(Digit::D1);
+        given[CellIdx::try_of_row_major(19).unwrap()] = Some(Digit::D1);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            GreedySolver::new()
+                .with_chess_moves(vec![ChessMove::Knight])
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+    }
+
+    #[test]
+    fn test_lines() {
+        let idx = |i: usize, j: usize| -> CellIdx {
+            (RowIdx::try_from(i).unwrap(), ColIdx::try_from(j).unwrap()).into()
+        };
+        let lines = vec![
+            Line::thermometer((0..6).map(|i| idx(i, i)).collect()).unwrap(),
+            Line::arrow(idx(8, 8), vec![idx(7, 7), idx(6, 7)]).unwrap(),
+            Line::palindrome((0..5).map(|i| idx(3 + i, i)).collect()).unwrap(),
+        ];
+        let solver = GreedySolver::new().with_lines(lines.clone());
+        let given = ArrGridRowMajor::new();
+        let complete = ArrGridRowMajor::with_diff(
+            &given,
+            solver
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+                .unwrap()
+                .into_iter(),
+        );
+        assert_eq!(
+            SudokuStatus::Complete,
+            eval_variants_status(&complete, solver.rules()).unwrap()
+        );
+
+        // The thermometer decreases.
+        let mut given = ArrGridRowMajor::new();
+        given[idx(0, 0)] = Some(Digit::D5);
+        given[idx(1, 1)] = Some(Digit::D3);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            solver.solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+        // The bulb leaves no room for the rest of the thermometer.
+        given[idx(1, 1)] = None;
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            solver.solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+    }
+
+    #[test]
+    fn test_pairs() {
+        let idx = |x| CellIdx::try_of_row_major(x).unwrap();
+        let given = ArrGridRowMajor::new();
+        [
+            Pair::non_consecutive(),
+            Pair::kropki(
+                &[(idx(0), idx(1)), (idx(40), idx(49))],
+                &[(idx(1), idx(10))],
+                false,
+            )
+            .unwrap(),
+            Pair::xv(
+                &[(idx(0), idx(1))],
+                &[(idx(79), idx(80)), (idx(61), idx(70))],
+                false,
+            )
+            .unwrap(),
+        ]
+        .into_iter()
+        .for_each(|pairs| {
+            let solver = GreedySolver::new().with_pairs(pairs.clone());
+            let complete = ArrGridRowMajor::with_diff(
+                &given,
+                solver
+                    .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+                    .unwrap()
+                    .into_iter(),
+            );
+            assert_eq!(
+                SudokuStatus::Complete,
+                eval_variants_status(&complete, solver.rules()).unwrap()
+            );
+        });
+
+        let mut given = ArrGridRowMajor::new();
+        given[idx(0)] = Some(Digit::D4);
+        given[idx(9)] = Some(Digit::D5);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            GreedySolver::new()
+                .with_pairs(Pair::non_consecutive())
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+    }
+
+    #[test]
+    fn test_greater_than() {
+        // Signs between every two cells sharing a side within a box, and no givens at all.
+        let solution: ArrGridRowMajor = read_from_string(
+            &RowMajorAscii::default(),
+            r#"
+534678912
+672195348
+198342567
+859761423
+426853791
+713924856
+961537284
+287419635
+345286179
+"#
+            .trim(),
+        )
+        .unwrap();
+        let signs = CellIdx::iter_row_wise()
+            .flat_map(|a| {
+                let b = [a.row_major() + 1, a.row_major() + 9]
+                    .into_iter()
+                    .filter_map(|x| CellIdx::try_of_row_major(x).ok())
+                    .filter(move |b| b.box_() == a.box_() && (a.row == b.row || a.col == b.col));
+                b.map(move |b| match solution[a] < solution[b] {
+                    true => (a, b),
+                    false => (b, a),
+                })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(9 * 12, signs.len());
+        let solver = GreedySolver::new().with_pairs(Pair::greater_than(&signs).unwrap());
+        let given = ArrGridRowMajor::new();
+        let complete = ArrGridRowMajor::with_diff(
+            &given,
+            solver
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+                .unwrap()
+                .into_iter(),
+        );
+        assert_eq!(
+            SudokuStatus::Complete,
+            eval_variants_status(&complete, solver.rules()).unwrap()
+        );
+
+        let mut given = ArrGridRowMajor::new();
+        given[signs[0].0] = Some(Digit::D9);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            solver.solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+    }
+
+    #[test]
+    fn test_clues() {
+        let clue = |kind, side, index, value| OutsideClue::new(kind, side, index, value).unwrap();
+        let given = ArrGridRowMajor::new();
+        [
+            vec![
+                clue(ClueKind::Sandwich, Side::Left, 0, 0),
+                clue(ClueKind::Sandwich, Side::Top, 4, 35),
+                clue(ClueKind::Sandwich, Side::Left, 6, 12),
+            ],
+            vec![
+                clue(ClueKind::Skyscraper, Side::Left, 0, 9),
+                clue(ClueKind::Skyscraper, Side::Top, 8, 1),
+                clue(ClueKind::Skyscraper, Side::Bottom, 8, 4),
+            ],
+            vec![
+                clue(ClueKind::XSum, Side::Right, 2, 45),
+                clue(ClueKind::XSum, Side::Top, 5, 1),
+                clue(ClueKind::XSum, Side::Bottom, 7, 20),
+            ],
+        ]
+        .into_iter()
+        .for_each(|clues| {
+            let solver = GreedySolver::new().with_clues(clues.clone());
+            let complete = ArrGridRowMajor::with_diff(
+                &given,
+                solver
+                    .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+                    .unwrap()
+                    .into_iter(),
+            );
+            assert_eq!(
+                SudokuStatus::Complete,
+                eval_variants_status(&complete, solver.rules()).unwrap()
+            );
+        });
+
+        let idx = |x| CellIdx::try_of_row_major(x).unwrap();
+        let mut given = ArrGridRowMajor::new();
+        given[idx(0)] = Some(Digit::D2);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            GreedySolver::new()
+                .with_clues(vec![clue(ClueKind::Skyscraper, Side::Left, 0, 9)])
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+    }
+
+    #[test]
+    fn test_latin() {
+        let idx = |x| CellIdx::try_of_row_major(x).unwrap();
+        // Both 1s are in the top-left box, which only matters with boxes.
+        let mut given = ArrGridRowMajor::new();
+        given[idx(0)] = Some(Digit::D1);
+        given[idx(10)] = Some(Digit::D1);
+        let solver = GreedySolver::new().without_boxes();
+        let complete = ArrGridRowMajor::with_diff(
+            &given,
+            solver
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+                .unwrap()
+                .into_iter(),
+        );
+        assert_eq!(
+            SudokuStatus::Complete,
+            eval_variants_status(&complete, solver.rules()).unwrap()
+        );
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            GreedySolver::new().solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+
+        given[idx(9)] = Some(Digit::D1);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            solver.solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+    }
+
+    #[test]
+    fn test_parity() {
+        let idx = |x| CellIdx::try_of_row_major(x).unwrap();
+        // Even digits down the main diagonal and odd ones along the start of the last row.
+        let parities = (0..9)
+            .map(|i| (idx(10 * i), Parity::Even))
+            .chain((72..76).map(|x| (idx(x), Parity::Odd)))
+            .collect::<Vec<_>>();
+        let solver = GreedySolver::new().with_parities(parities.clone());
+        let given = ArrGridRowMajor::new();
+        let complete = ArrGridRowMajor::with_diff(
+            &given,
+            solver
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+                .unwrap()
+                .into_iter(),
+        );
+        assert_eq!(
+            SudokuStatus::Complete,
+            eval_variants_status(&complete, solver.rules()).unwrap()
+        );
+
+        let mut given = ArrGridRowMajor::new();
+        given[idx(72)] = Some(Digit::D4);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            solver.solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+    }
+
+    /// House rule: the cells hold the same digit.
+    #[derive(Debug)]
+    struct Same(Vec<CellIdx>);
+
+    impl Constraint for Same {
+        fn cells(&self) -> &[CellIdx] {
+            &self.0
+        }
+
+        fn prune(&self, candidates: &mut [u16; CellIdx::COUNT]) {
+            let common = self
+                .0
+                .iter()
+                .fold(u16::MAX, |acc, idx| acc & candidates[idx.row_major()]);
+            self.0
+                .iter()
+                .for_each(|idx| candidates[idx.row_major()] = common);
+        }
+
+        fn validate(&self, grid: &dyn Index<CellIdx, Output = Option<Digit>>) -> bool {
+            self.0.iter().filter_map(|idx| grid[*idx]).all_equal()
+        }
+    }
+
+    #[test]
+    fn test_constraints() {
+        let idx = |x| CellIdx::try_of_row_major(x).unwrap();
+        let same = || Box::new(Same(vec![idx(0), idx(40), idx(80), idx(33)])) as Box<_>;
+        let given = ArrGridRowMajor::new();
+        let complete = ArrGridRowMajor::with_diff(
+            &given,
+            GreedySolver::new()
+                .with_constraints(vec![same()])
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+                .unwrap()
+                .into_iter(),
+        );
+        assert_eq!(SudokuStatus::Complete, eval_status(&complete).unwrap());
+        assert!(same().validate(&complete));
+
+        let mut given = ArrGridRowMajor::new();
+        given[idx(0)] = Some(Digit::D1);
+        given[idx(40)] = Some(Digit::D2);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            GreedySolver::new()
+                .with_constraints(vec![same()])
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+        // Cells of a row never hold the same digit.
+        let given = ArrGridRowMajor::new();
+        assert_eq!(
+            Err(SolverError::Infeasible),
+            GreedySolver::new()
+                .with_constraints(vec![Box::new(Same(vec![idx(0), idx(1)]))])
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+    }
+
+    #[test]
+    fn test_regions() {
+        let given = ArrGridRowMajor::new();
+        [Region::diagonals(), Region::windows()]
+            .into_iter()
+            .for_each(|regions| {
+                let solver = GreedySolver::new().with_regions(regions.clone());
+                let complete = ArrGridRowMajor::with_diff(
+                    &given,
+                    solver
+                        .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+                        .unwrap()
+                        .into_iter(),
+                );
+                assert_eq!(
+                    SudokuStatus::Complete,
+                    eval_variants_status(&complete, solver.rules()).unwrap()
+                );
+            });
+
+        // Both 1s are fine for rows, columns and boxes, but not for the main diagonal.
+        let mut given = ArrGridRowMajor::new();
+        given[CellIdx::try_of_row_major(0).unwrap()] = Some(Digit::D1);
+        given[CellIdx::try_of_row_major(30).unwrap()] = Some(Digit::D1);
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            GreedySolver::new()
+                .with_regions(Region::diagonals())
+                .solve::<_, _, Vec<_>>(&NeverCancelled::new(), &given)
+        );
+    }
+
     #[test]
     fn test_infeasible() {
         let given = r#"
@@ -757,7 +1275,7 @@ _________
 
     #[test]
     fn test_fuzzing_crash_2() {
-        // TODO(kostya): times out, but should be infeasible
+        // Infeasible, which branching on every empty cell in turn took too long to prove.
         let given = r#"
 3417_6___
 ____958__
@@ -768,23 +1286,74 @@ ___958___
 _958__2__
 ____7_6__
 ______958
-"#
-        .trim();
-        let expected = r#"
-_________
-_________
-_________
-_________
-_________
-_________
-_________
-_________
-_________
 "#
         .trim();
         let given: ArrGridRowMajor = read_from_string(&RowMajorAscii::default(), given).unwrap();
         let complete = solve_with_timeout(&given, Duration::from_secs(1))
             .map(|grid| write_string(&RowMajorAscii::default(), &grid));
-        assert_eq!(&expected, &complete.unwrap());
+        assert_eq!(Err(SolverError::Infeasible), complete);
+    }
+
+    fn assert_solves_sized<S>(given: &ArrGridRowMajor<S>)
+    where
+        S: Shape<Digit = u8>,
+    {
+        let diff: Vec<_> = GreedySolver::<S>::default()
+            .solve_sized(&NeverCancelled::new(), given)
+            .unwrap();
+        let mut solution = given.clone();
+        diff.into_iter().for_each(|(idx, value)| {
+            assert_eq!(None, given[idx]);
+            solution[idx] = Some(value);
+        });
+        assert_eq!(
+            Ok(SudokuStatus::Complete),
+            eval_sized_status(&solution).map_err(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_sized_empty() {
+        assert_solves_sized(&ArrGridRowMajor::<Shape4x4>::default());
+        assert_solves_sized(&ArrGridRowMajor::<Shape6x6>::default());
+        assert_solves_sized(&ArrGridRowMajor::<Shape16x16>::default());
+        assert_solves_sized(&ArrGridRowMajor::<Shape25x25>::default());
+    }
+
+    #[test]
+    fn test_sized_6x6() {
+        let given = r#"
+__3_1_
+1____4
+____6_
+_2____
+5____3
+_6_4__
+"#
+        .trim();
+        let given = RowMajorAscii::<Shape6x6>::default()
+            .read_sized_from_string(given)
+            .unwrap();
+        assert_solves_sized(&given);
+    }
+
+    #[test]
+    fn test_sized_errors() {
+        let f = RowMajorAscii::<Shape4x4>::default();
+        let solve = |s: &str| {
+            GreedySolver::<Shape4x4>::default().solve_sized::<_, Vec<_>>(
+                &NeverCancelled::new(),
+                &f.read_sized_from_string(s).unwrap(),
+            )
+        };
+        assert_eq!(
+            Err(SolverError::ConstraintsViolated),
+            solve("11__\n____\n____\n____")
+        );
+        // The top-left cell can hold neither 1, 2, 3 nor 4.
+        assert_eq!(
+            Err(SolverError::Infeasible),
+            solve("_12_\n3___\n____\n4___")
+        );
     }
 }
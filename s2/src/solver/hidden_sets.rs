@@ -2,6 +2,7 @@ use crate::grid::DIM;
 use crate::util::{BoolMatrix9x9, Domain};
 use std::iter::zip;
 
+#[allow(dead_code)]
 #[derive(Debug, Default)]
 pub struct HiddenSets<T>
 where
@@ -11,6 +12,7 @@ where
     eq: BoolMatrix9x9,
 }
 
+#[allow(dead_code)]
 impl<T> HiddenSets<T>
 where
     T: Default,
@@ -63,8 +65,7 @@ where
         F: for<'a> FnOnce(Domain, &'a [T]) -> R,
     {
         zip(self.elts_per_digit.iter(), self.eq.iter_rows())
-            .filter(|(elts, eq)| elts.0 == size && eq.count_ones() == size)
-            .next()
+            .find(|(elts, eq)| elts.0 == size && eq.count_ones() == size)
             .map(|(elts, eq)| f((!eq).into(), &elts.1[..(elts.0 as usize)]))
     }
 }
@@ -3,13 +3,25 @@ use crate::grid::{Grid, GridDiff};
 use std::iter::FromIterator;
 use thiserror::Error;
 
+mod constraints;
+pub(crate) use constraints::Constraints;
+
 mod hidden_sets;
 use hidden_sets::HiddenSets;
 
 mod greedy_solver;
 pub use greedy_solver::GreedySolver;
 
+mod counting_solver;
+pub use counting_solver::CountingSolver;
+
+mod backtracking;
+
+mod composite_solver;
+pub use composite_solver::CompositeSolver;
+
 #[derive(Debug, Default, Error, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[error("Sudoku is either infeasible or constraints are already violated")]
 pub enum SolverError {
     #[error("infeasible")]
@@ -1,4 +1,5 @@
-use crate::grid::{CellIdx, Digit, RowIdx, ColIdx};
+use crate::constraint::Rules;
+use crate::grid::{ArrGridRowMajor, CellIdx, ColIdx, CompositeGrid, Digit, RowIdx, Shape};
 use itertools::Itertools;
 use std::iter::{repeat, zip};
 use std::ops::{Index, IndexMut};
@@ -6,6 +7,7 @@ use strum::{EnumCount, IntoEnumIterator};
 use strum_macros::Display as DisplayMacros;
 
 #[derive(Debug, Default, DisplayMacros, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SudokuStatus {
     #[default]
     Incomplete,
@@ -86,7 +88,7 @@ impl Counter {
 
 pub fn eval_status<T>(grid: &T) -> Result<SudokuStatus, SudokuStatusError>
 where
-    T: Index<CellIdx, Output = Option<Digit>>,
+    T: Index<CellIdx, Output = Option<Digit>> + ?Sized,
 {
     let rows = RowIdx::iter()
         .map(|i| {
@@ -125,3 +127,511 @@ where
         .try_fold(true, |acc, x| x.map(|x| acc && x))?;
     Ok((rows && cols && sub3x3s).into())
 }
+
+/// Same as `eval_status` under `rules`, the ones `GreedySolver` solves for: incomplete while
+/// cells are empty, complete once every cell is set, and an error as soon as the set digits
+/// break any of them.
+pub fn eval_variants_status<T>(grid: &T, rules: &Rules) -> Result<SudokuStatus, SudokuStatusError>
+where
+    T: Index<CellIdx, Output = Option<Digit>>,
+{
+    if !rules.validate(grid) {
+        return Err(SudokuStatusError);
+    }
+    Ok(CellIdx::iter_row_wise()
+        .all(|idx| grid[idx].is_some())
+        .into())
+}
+
+/// Same as `eval_status` for every grid of a composite puzzle, complete once all of them are.
+pub fn eval_composite_status(grid: &CompositeGrid) -> Result<SudokuStatus, SudokuStatusError> {
+    (0..grid.layout().origins().len()).try_fold(SudokuStatus::Complete, |acc, i| {
+        let status = eval_status(&grid.grid(i))?;
+        Ok((bool::from(acc) && bool::from(status)).into())
+    })
+}
+
+/// Same as `eval_status` for a grid of any `Shape`.
+pub fn eval_sized_status<S>(grid: &ArrGridRowMajor<S>) -> Result<SudokuStatus, SudokuStatusError>
+where
+    S: Shape,
+{
+    let mut complete = true;
+    for unit in S::iter_units() {
+        let mut seen = 0u32;
+        for value in unit
+            .into_iter()
+            .filter_map(|idx| grid[idx].map(Into::<u8>::into))
+        {
+            if seen & (1 << value) != 0 {
+                return Err(SudokuStatusError);
+            }
+            seen |= 1 << value;
+        }
+        complete &= seen.count_ones() as usize == S::DIM;
+    }
+    Ok(complete.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        eval_composite_status, eval_sized_status, eval_status, eval_variants_status, SudokuStatus,
+    };
+    use crate::constraint::{Distinct, Rules, Variants};
+    use crate::format::{read_from_string, BoxLayoutAscii, RowMajorAscii};
+    use crate::grid::{
+        ArrGridRowMajor, Cage, CellIdx, ChessMove, ClueKind, ColIdx, CompositeGrid,
+        CompositeLayout, Digit, Line, OutsideClue, Pair, Parity, Region, RowIdx, Shape4x4,
+        Shape6x6, Side, SizedCellIdx,
+    };
+
+    fn idx(i: usize, j: usize) -> CellIdx {
+        (RowIdx::try_from(i).unwrap(), ColIdx::try_from(j).unwrap()).into()
+    }
+
+    #[test]
+    fn test_jigsaw() {
+        let layout = BoxLayoutAscii::default()
+            .read_from_string(
+                r#"
+AAABBBCCC
+AAAABBCCC
+AADBBBCCC
+DDDEBEFFF
+DDEEEEFFF
+DDDEEEFFF
+GGGHHHIII
+GGGHHHIII
+GGGHHHIII
+"#,
+            )
+            .unwrap();
+        let jigsaw = Rules::from(Variants {
+            layout,
+            ..Default::default()
+        });
+        let solution: ArrGridRowMajor = read_from_string(
+            &RowMajorAscii::default(),
+            r#"
+123456789
+456789123
+891237456
+234518697
+567192348
+789364215
+312875964
+645923871
+978641532
+"#
+            .trim(),
+        )
+        .unwrap();
+        assert_eq!(
+            Ok(SudokuStatus::Complete),
+            eval_variants_status(&solution, &jigsaw).map_err(|_| ())
+        );
+        // 1 is twice in the classic top-left box.
+        assert!(super::eval_status(&solution).is_err());
+        let mut grid = solution;
+        grid[idx(4, 4)] = None;
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            eval_variants_status(&grid, &jigsaw).map_err(|_| ())
+        );
+        // Fine for classic boxes, but both in the first jigsaw box.
+        let mut grid = ArrGridRowMajor::new();
+        grid[idx(1, 3)] = Some(Digit::D1);
+        grid[idx(2, 0)] = Some(Digit::D1);
+        assert!(super::eval_status(&grid).is_ok());
+        assert!(eval_variants_status(&grid, &jigsaw).is_err());
+    }
+
+    #[test]
+    fn test_chess() {
+        let mut grid = ArrGridRowMajor::new();
+        grid[idx(0, 0)] = Some(Digit::D1);
+        grid[idx(1, 2)] = Some(Digit::D2);
+        grid[idx(4, 4)] = Some(Digit::D1);
+        let knight = Rules::from(Variants {
+            moves: vec![ChessMove::Knight],
+            ..Default::default()
+        });
+        let king = Rules::from(Variants {
+            moves: vec![ChessMove::King],
+            ..Default::default()
+        });
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            eval_variants_status(&grid, &knight).map_err(|_| ())
+        );
+        grid[idx(2, 5)] = Some(Digit::D1);
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            eval_variants_status(&grid, &king).map_err(|_| ())
+        );
+        // A knight's move away from the 1 in the middle.
+        assert!(eval_variants_status(&grid, &knight).is_err());
+        grid[idx(2, 5)] = None;
+        grid[idx(3, 3)] = Some(Digit::D3);
+        assert!(eval_variants_status(&grid, &knight).is_ok());
+        grid[idx(5, 3)] = Some(Digit::D1);
+        assert!(eval_variants_status(&grid, &king).is_err());
+    }
+
+    #[test]
+    fn test_lines() {
+        let lines = Rules::from(Variants {
+            lines: vec![
+                Line::thermometer(vec![idx(0, 0), idx(1, 1), idx(2, 2)]).unwrap(),
+                Line::arrow(idx(4, 4), vec![idx(4, 5), idx(5, 5)]).unwrap(),
+                Line::palindrome(vec![idx(6, 0), idx(7, 1), idx(8, 2), idx(7, 3)]).unwrap(),
+            ],
+            ..Default::default()
+        });
+        let mut grid = ArrGridRowMajor::new();
+        grid[idx(1, 1)] = Some(Digit::D2);
+        grid[idx(4, 4)] = Some(Digit::D7);
+        grid[idx(4, 5)] = Some(Digit::D4);
+        grid[idx(6, 0)] = Some(Digit::D5);
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            eval_variants_status(&grid, &lines).map_err(|_| ())
+        );
+        grid[idx(5, 5)] = Some(Digit::D3);
+        assert!(eval_variants_status(&grid, &lines).is_ok());
+        // The arrow adds up to 8.
+        grid[idx(5, 5)] = Some(Digit::D4);
+        assert!(eval_variants_status(&grid, &lines).is_err());
+        grid[idx(5, 5)] = None;
+        // Nothing is left for the bulb below 2.
+        grid[idx(1, 1)] = Some(Digit::D1);
+        assert!(eval_variants_status(&grid, &lines).is_err());
+        grid[idx(1, 1)] = Some(Digit::D2);
+        grid[idx(7, 3)] = Some(Digit::D6);
+        assert!(eval_variants_status(&grid, &lines).is_err());
+        grid[idx(7, 3)] = Some(Digit::D5);
+        assert!(eval_variants_status(&grid, &lines).is_ok());
+    }
+
+    #[test]
+    fn test_pairs() {
+        let status = |grid: &ArrGridRowMajor, pairs: Vec<Pair>| {
+            eval_variants_status(
+                grid,
+                &Rules::from(Variants {
+                    pairs,
+                    ..Default::default()
+                }),
+            )
+        };
+        let kropki =
+            Pair::kropki(&[(idx(0, 0), idx(0, 1))], &[(idx(1, 0), idx(0, 0))], true).unwrap();
+        let mut grid = ArrGridRowMajor::new();
+        grid[idx(0, 0)] = Some(Digit::D3);
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            status(&grid, kropki.clone()).map_err(|_| ())
+        );
+        grid[idx(0, 1)] = Some(Digit::D4);
+        grid[idx(1, 0)] = Some(Digit::D6);
+        assert!(status(&grid, kropki.clone()).is_ok());
+        // Consecutive without a white dot.
+        grid[idx(1, 1)] = Some(Digit::D5);
+        assert!(status(&grid, kropki.clone()).is_err());
+        assert!(status(&grid, Pair::non_consecutive()).is_err());
+        grid[idx(1, 1)] = Some(Digit::D1);
+        assert!(status(&grid, kropki.clone()).is_ok());
+
+        // 5 can't be on an X whatever its other cell.
+        let xv = Pair::xv(&[(idx(0, 0), idx(0, 1))], &[], false).unwrap();
+        let mut grid = ArrGridRowMajor::new();
+        grid[idx(0, 0)] = Some(Digit::D5);
+        assert!(status(&grid, xv.clone()).is_err());
+
+        // 9 can't be the smaller digit of a sign, nor 2 the larger one next to 3.
+        let signs = Pair::greater_than(&[(idx(0, 0), idx(0, 1))]).unwrap();
+        assert!(status(&grid, signs.clone()).is_ok());
+        grid[idx(0, 0)] = Some(Digit::D9);
+        assert!(status(&grid, signs.clone()).is_err());
+        grid[idx(0, 0)] = Some(Digit::D3);
+        grid[idx(0, 1)] = Some(Digit::D2);
+        assert!(status(&grid, signs.clone()).is_err());
+    }
+
+    #[test]
+    fn test_clues() {
+        let status = |grid: &ArrGridRowMajor, clues: &[OutsideClue]| {
+            eval_variants_status(
+                grid,
+                &Rules::from(Variants {
+                    clues: clues.to_vec(),
+                    ..Default::default()
+                }),
+            )
+        };
+        let clue = |kind, side, index, value| OutsideClue::new(kind, side, index, value).unwrap();
+        let clues = [
+            clue(ClueKind::Sandwich, Side::Left, 0, 35),
+            clue(ClueKind::Skyscraper, Side::Left, 0, 9),
+            clue(ClueKind::XSum, Side::Right, 0, 45),
+        ];
+        let mut grid = ArrGridRowMajor::new();
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            status(&grid, &clues).map_err(|_| ())
+        );
+        grid[idx(0, 0)] = Some(Digit::D1);
+        grid[idx(0, 8)] = Some(Digit::D9);
+        assert!(status(&grid, &clues).is_ok());
+        // 3 hides 2 from the left, so fewer than 9 digits are seen.
+        grid[idx(0, 1)] = Some(Digit::D3);
+        assert!(status(&grid, &clues).is_err());
+        // 1 and 9 next to each other sandwich nothing.
+        let sandwich = [clue(ClueKind::Sandwich, Side::Top, 4, 10)];
+        grid[idx(3, 4)] = Some(Digit::D1);
+        grid[idx(4, 4)] = Some(Digit::D9);
+        assert!(status(&grid, &sandwich).is_err());
+    }
+
+    #[test]
+    fn test_latin() {
+        let latin = Rules::from(Variants {
+            latin: true,
+            ..Default::default()
+        });
+        // Cyclic shifts by one make a Latin square, but repeat digits in every box.
+        let mut grid = ArrGridRowMajor::new();
+        for idx in CellIdx::iter_row_wise() {
+            let (i, j): (usize, usize) = (idx.row.into(), idx.col.into());
+            grid[idx] = Some(Digit::try_from(((i + j) % 9) as u8).unwrap());
+        }
+        assert_eq!(
+            Ok(SudokuStatus::Complete),
+            eval_variants_status(&grid, &latin).map_err(|_| ())
+        );
+        assert!(eval_status(&grid).is_err());
+        grid[idx(4, 4)] = None;
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            eval_variants_status(&grid, &latin).map_err(|_| ())
+        );
+        grid[idx(4, 5)] = Some(Digit::D8);
+        assert!(eval_variants_status(&grid, &latin).is_err());
+    }
+
+    #[test]
+    fn test_parity() {
+        let status = |grid: &ArrGridRowMajor, parities: &[(CellIdx, Parity)]| {
+            eval_variants_status(
+                grid,
+                &Rules::from(Variants {
+                    parities: parities.to_vec(),
+                    ..Default::default()
+                }),
+            )
+        };
+        let parities = [(idx(0, 0), Parity::Odd), (idx(4, 4), Parity::Even)];
+        let mut grid = ArrGridRowMajor::new();
+        grid[idx(0, 0)] = Some(Digit::D9);
+        grid[idx(4, 4)] = Some(Digit::D2);
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            status(&grid, &parities).map_err(|_| ())
+        );
+        grid[idx(4, 4)] = Some(Digit::D5);
+        assert!(status(&grid, &parities).is_err());
+        assert!(status(&grid, &parities[..1]).is_ok());
+    }
+
+    #[test]
+    fn test_regions() {
+        let read = |s: &str| -> ArrGridRowMajor {
+            read_from_string(&RowMajorAscii::default(), s.trim()).unwrap()
+        };
+        let status = |grid: &ArrGridRowMajor| {
+            let diagonals = Rules::from(Variants {
+                regions: Region::diagonals(),
+                ..Default::default()
+            });
+            eval_variants_status(grid, &diagonals).map_err(|_| ())
+        };
+        // A Sudoku-X solution.
+        let solution = read(
+            r#"
+123456789
+456789123
+789123456
+935241867
+617538294
+842697531
+298314675
+371865942
+564972318
+"#,
+        );
+        assert_eq!(
+            Ok(SudokuStatus::Complete),
+            super::eval_status(&solution).map_err(|_| ())
+        );
+        let mut grid = solution;
+        grid[idx(0, 0)] = None;
+        assert_eq!(Ok(SudokuStatus::Incomplete), status(&grid));
+        // Fine for rows, columns and boxes, but repeats 1 on the main diagonal.
+        let grid = read(
+            r#"
+1________
+_________
+_________
+___1_____
+_________
+_________
+_________
+_________
+_________
+"#,
+        );
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            super::eval_status(&grid).map_err(|_| ())
+        );
+        assert!(status(&grid).is_err());
+        assert_eq!(Ok(SudokuStatus::Complete), status(&solution));
+    }
+
+    #[test]
+    fn test_composite() {
+        let at = |row, col| SizedCellIdx { row, col };
+        let mut grid = CompositeGrid::new(CompositeLayout::twodoku());
+        grid[at(8, 8)] = Some(Digit::D4);
+        grid[at(8, 14)] = Some(Digit::D5);
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            eval_composite_status(&grid).map_err(|_| ())
+        );
+        // Same row of the second grid only.
+        grid[at(8, 12)] = Some(Digit::D4);
+        assert!(eval_composite_status(&grid).is_err());
+    }
+
+    #[test]
+    fn test_constraints() {
+        let mut grid = ArrGridRowMajor::new();
+        let status = |grid: &ArrGridRowMajor| eval_variants_status(grid, &Rules::default()).ok();
+        assert_eq!(Some(SudokuStatus::Incomplete), status(&grid));
+        grid[idx(0, 0)] = Some(Digit::D1);
+        grid[idx(2, 2)] = Some(Digit::D1);
+        assert!(eval_status(&grid).is_err());
+        assert_eq!(None, status(&grid));
+
+        let diagonal = Rules::from(Variants {
+            constraints: vec![Box::new(Distinct::new((0..9).map(|i| idx(i, i)).collect()))],
+            ..Default::default()
+        });
+        assert!(eval_variants_status(&grid, &diagonal).is_err());
+        grid[idx(2, 2)] = None;
+        grid[idx(4, 1)] = Some(Digit::D1);
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            eval_variants_status(&grid, &diagonal).map_err(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_variants() {
+        let rules = Rules::from(Variants {
+            latin: true,
+            cages: vec![Cage::new(vec![idx(0, 0), idx(0, 1)], 3).unwrap()],
+            parities: vec![(idx(4, 4), Parity::Even)],
+            ..Default::default()
+        });
+        let mut grid = ArrGridRowMajor::new();
+        grid[idx(0, 0)] = Some(Digit::D1);
+        grid[idx(0, 1)] = Some(Digit::D2);
+        // Repeats 1 in the top-left box, which a Latin square doesn't have.
+        grid[idx(1, 1)] = Some(Digit::D1);
+        assert!(eval_status(&grid).is_err());
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            eval_variants_status(&grid, &rules).map_err(|_| ())
+        );
+        grid[idx(4, 4)] = Some(Digit::D3);
+        assert!(eval_variants_status(&grid, &rules).is_err());
+        grid[idx(4, 4)] = Some(Digit::D4);
+        grid[idx(0, 1)] = Some(Digit::D3);
+        assert!(eval_variants_status(&grid, &rules).is_err());
+    }
+
+    #[test]
+    fn test_killer() {
+        let solution: ArrGridRowMajor = read_from_string(
+            &RowMajorAscii::default(),
+            r#"
+534678912
+672195348
+198342567
+859761423
+426853791
+713924856
+961537284
+287419635
+345286179
+"#
+            .trim(),
+        )
+        .unwrap();
+        let status = |grid: &ArrGridRowMajor, cages: &[Cage]| {
+            let killer = Rules::from(Variants {
+                cages: cages.to_vec(),
+                ..Default::default()
+            });
+            eval_variants_status(grid, &killer).map_err(|_| ())
+        };
+        let cage = |cells: Vec<CellIdx>, sum| Cage::new(cells, sum).unwrap();
+        assert_eq!(
+            Ok(SudokuStatus::Complete),
+            status(&solution, &[cage(vec![idx(0, 0), idx(1, 0)], 11)])
+        );
+        assert!(status(&solution, &[cage(vec![idx(0, 0), idx(1, 0)], 12)]).is_err());
+
+        let mut grid = ArrGridRowMajor::new();
+        // Cells of the cage share no row, column or box.
+        let cages = [cage(vec![idx(0, 0), idx(3, 4)], 11)];
+        grid[idx(0, 0)] = Some(Digit::D5);
+        assert_eq!(Ok(SudokuStatus::Incomplete), status(&grid, &cages));
+        grid[idx(3, 4)] = Some(Digit::D5);
+        assert!(status(&grid, &cages).is_err());
+        grid[idx(0, 0)] = Some(Digit::D9);
+        assert!(status(&grid, &cages).is_err());
+        grid[idx(3, 4)] = None;
+        assert_eq!(Ok(SudokuStatus::Incomplete), status(&grid, &cages));
+        grid[idx(0, 0)] = Some(Digit::D1);
+        assert!(status(&grid, &cages).is_err());
+        grid[idx(3, 4)] = Some(Digit::D9);
+        assert!(status(&grid, &cages).is_err());
+        grid[idx(0, 0)] = Some(Digit::D2);
+        assert_eq!(Ok(SudokuStatus::Incomplete), status(&grid, &cages));
+    }
+
+    #[test]
+    fn test_sized() {
+        let mut grid = RowMajorAscii::<Shape4x4>::default()
+            .read_sized_from_string("1234\n3412\n2143\n4321")
+            .unwrap();
+        assert_eq!(
+            Ok(SudokuStatus::Complete),
+            eval_sized_status(&grid).map_err(|_| ())
+        );
+        grid[SizedCellIdx { row: 0, col: 0 }] = None;
+        assert_eq!(
+            Ok(SudokuStatus::Incomplete),
+            eval_sized_status(&grid).map_err(|_| ())
+        );
+
+        // Rows and columns are fine, the top-left 2x3 box is not.
+        let grid = RowMajorAscii::<Shape6x6>::default()
+            .read_sized_from_string("1_____\n_1____\n______\n______\n______\n______")
+            .unwrap();
+        assert!(eval_sized_status(&grid).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use super::{invert, Permutation, Transformation, BOX};
+use crate::grid::{ArrGridRowMajor, CellIdx, ColIdx, Digit, Grid, GridMutWithDefault, RowIdx, DIM};
+use itertools::Itertools;
+use std::array;
+use std::cmp::Ordering;
+use std::sync::LazyLock;
+
+/// Every band-preserving permutation, as the input line placed at each output line.
+static LINE_PERMUTATIONS: LazyLock<Vec<Permutation>> = LazyLock::new(|| {
+    let triples = (0..BOX).permutations(BOX).collect::<Vec<_>>();
+    triples
+        .iter()
+        .cartesian_product(triples.iter())
+        .cartesian_product(triples.iter())
+        .cartesian_product(triples.iter())
+        .map(|(((bands, first), second), third)| {
+            let within = [first, second, third];
+            array::from_fn(|x| (bands[x / BOX] * BOX + within[x / BOX][x % BOX]) as u8)
+        })
+        .collect()
+});
+
+/// Cell values with empty cells as 0 and digits as 1 to 9.
+type Lines = [[u8; DIM]; DIM];
+
+#[derive(Debug)]
+struct Search {
+    lines: Lines,
+    transpose: bool,
+    cols: Permutation,
+    rows: Permutation,
+    current: Lines,
+    best: Option<(Lines, Transformation)>,
+    /// Every transformation reaching the best grid so far, when collecting.
+    ties: Option<Vec<Transformation>>,
+}
+
+impl Search {
+    fn new(collect: bool) -> Self {
+        Self {
+            lines: [[0; DIM]; DIM],
+            transpose: false,
+            cols: [0; DIM],
+            rows: [0; DIM],
+            current: [[0; DIM]; DIM],
+            best: None,
+            ties: collect.then(Vec::new),
+        }
+    }
+
+    fn transformation(&self, labels: &[u8; DIM + 1]) -> Transformation {
+        // Digits missing from a puzzle take the remaining labels in order.
+        let mut digits = [0; DIM];
+        let mut unused = (1..=DIM as u8).filter(|label| !labels[1..].contains(label));
+        (0..DIM).for_each(|d| {
+            digits[d] = match labels[d + 1] {
+                0 => unused.next().unwrap(),
+                label => label,
+            } - 1
+        });
+        let (rows, cols) = if self.transpose {
+            (invert(&self.cols), invert(&self.rows))
+        } else {
+            (invert(&self.rows), invert(&self.cols))
+        };
+        Transformation {
+            transpose: self.transpose,
+            rows,
+            cols,
+            digits,
+        }
+    }
+
+    /// Places input rows at output rows `row..`, keeping bands together. Labels digits in order of
+    /// first appearance, which is the smallest relabeling for the chosen cell positions. Returns
+    /// whether a new best grid was found.
+    fn place(&mut self, row: usize, used: u16, labels: [u8; DIM + 1], mut less: bool) -> bool {
+        let band = if row.is_multiple_of(BOX) {
+            None
+        } else {
+            Some(self.rows[row - 1] as usize / BOX)
+        };
+        let candidates = (0..DIM)
+            .filter(|src| used & (1u16 << src) == 0)
+            .filter(|src| band.is_none_or(|band| src / BOX == band));
+        let mut updated = false;
+        for src in candidates {
+            let mut labels = labels;
+            let mut next = labels.iter().max().unwrap() + 1;
+            let out: [u8; DIM] =
+                array::from_fn(|col| match self.lines[src][self.cols[col] as usize] {
+                    0 => 0,
+                    value => {
+                        if labels[value as usize] == 0 {
+                            labels[value as usize] = next;
+                            next += 1;
+                        }
+                        labels[value as usize]
+                    }
+                });
+            let child_less = match (less, &self.best) {
+                (true, _) | (_, None) => true,
+                (false, Some((best, _))) => match out.cmp(&best[row]) {
+                    Ordering::Greater => continue,
+                    Ordering::Less => true,
+                    Ordering::Equal => false,
+                },
+            };
+            self.current[row] = out;
+            self.rows[row] = src as u8;
+            let found = if row == DIM - 1 {
+                let transformation = self.transformation(&labels);
+                let improved = child_less || self.best.is_none();
+                if improved {
+                    self.best = Some((self.current, transformation));
+                }
+                if let Some(ties) = &mut self.ties {
+                    if improved {
+                        ties.clear();
+                    }
+                    ties.push(transformation);
+                }
+                improved
+            } else {
+                self.place(row + 1, used | (1u16 << src), labels, child_less)
+            };
+            if found {
+                // The new best shares the prefix placed so far.
+                less = false;
+                updated = true;
+            }
+        }
+        updated
+    }
+}
+
+fn search<T>(grid: &T, collect: bool) -> Search
+where
+    T: Grid + ?Sized,
+{
+    let value = |i: usize, j: usize| {
+        let idx = (RowIdx::try_from(i).unwrap(), ColIdx::try_from(j).unwrap()).into();
+        grid[idx].map_or(0, |x| u8::from(x) + 1)
+    };
+    let mut search = Search::new(collect);
+    for transpose in [false, true] {
+        search.transpose = transpose;
+        search.lines = array::from_fn(|i| {
+            array::from_fn(|j| if transpose { value(j, i) } else { value(i, j) })
+        });
+        for cols in LINE_PERMUTATIONS.iter() {
+            search.cols = *cols;
+            search.place(0, 0, [0; DIM + 1], false);
+        }
+    }
+    search
+}
+
+fn to_grid(lines: &Lines) -> ArrGridRowMajor {
+    ArrGridRowMajor::of_set(CellIdx::iter_row_wise().filter_map(|idx| {
+        let value = lines[usize::from(idx.row)][usize::from(idx.col)];
+        Some((idx, Digit::try_from(value.checked_sub(1)?).unwrap()))
+    }))
+}
+
+/// Lexicographically smallest grid equivalent to `grid`, along with the transformation that maps
+/// `grid` onto it. Grids are compared row-major with empty cells before digits. Equivalence is
+/// the full validity-preserving group: row, column, band and stack permutations, transposition
+/// and digit relabeling.
+pub fn canonicalize<T>(grid: &T) -> (ArrGridRowMajor, Transformation)
+where
+    T: Grid + ?Sized,
+{
+    let (lines, transformation) = search(grid, false).best.unwrap();
+    (to_grid(&lines), transformation)
+}
+
+/// Same as `canonicalize`, returning every transformation that maps `grid` onto its canonical
+/// form. Digits absent from `grid` are labeled in order, so they are never permuted among
+/// themselves.
+pub(crate) fn canonicalize_all<T>(grid: &T) -> (ArrGridRowMajor, Vec<Transformation>)
+where
+    T: Grid + ?Sized,
+{
+    let search = search(grid, true);
+    let (lines, _) = search.best.unwrap();
+    (to_grid(&lines), search.ties.unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use super::canonicalize;
+    use crate::format::{read_from_string, write_string, RowMajorAscii};
+    use crate::grid::ArrGridRowMajor;
+    use crate::transform::Transformation;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn read(grid: &str) -> ArrGridRowMajor {
+        read_from_string(&RowMajorAscii::default(), grid.trim()).unwrap()
+    }
+
+    fn assert_canonical(grid: &ArrGridRowMajor) {
+        let (canonical, t) = canonicalize(grid);
+        assert_eq!(canonical, t.apply::<_, ArrGridRowMajor>(grid));
+        let mut rng = StdRng::seed_from_u64(0);
+        (0..8).for_each(|_| {
+            let disguised: ArrGridRowMajor = Transformation::random(&mut rng).apply(grid);
+            let (other, t) = canonicalize(&disguised);
+            assert_eq!(canonical, other);
+            assert_eq!(canonical, t.apply::<_, ArrGridRowMajor>(&disguised));
+        });
+    }
+
+    #[test]
+    fn test_solution() {
+        let grid = read(
+            r#"
+534678912
+672195348
+198342567
+859761423
+426853791
+713924856
+961537284
+287419635
+345286179
+"#,
+        );
+        assert_canonical(&grid);
+        let (canonical, _) = canonicalize(&grid);
+        assert_eq!(
+            "123456789",
+            write_string(&RowMajorAscii::default(), &canonical)
+                .lines()
+                .next()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_puzzle() {
+        let grid = read(
+            r#"
+53__7____
+6__195___
+_98____6_
+8___6___3
+4__8_3__1
+7___2___6
+_6____28_
+___419__5
+____8__79
+"#,
+        );
+        assert_canonical(&grid);
+    }
+
+    #[test]
+    fn test_empty() {
+        let (canonical, t) = canonicalize(&ArrGridRowMajor::new());
+        assert_eq!(ArrGridRowMajor::new(), canonical);
+        assert_eq!(
+            ArrGridRowMajor::new(),
+            t.apply::<_, ArrGridRowMajor>(&canonical)
+        );
+    }
+}
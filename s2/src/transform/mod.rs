@@ -0,0 +1,470 @@
+use crate::grid::{CellIdx, ColIdx, Digit, Grid, GridDiff, GridMutWithDefault, RowIdx, DIM};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::array;
+use thiserror::Error;
+
+mod canonical;
+pub use canonical::canonicalize;
+pub(crate) use canonical::canonicalize_all;
+
+const BOX: usize = 3;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum TransformationError {
+    #[error("not a permutation")]
+    NotAPermutation,
+    #[error("band or stack index")]
+    Band,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// 90° clockwise.
+    Quarter,
+    Half,
+    /// 270° clockwise.
+    ThreeQuarters,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// Swaps the left and right halves.
+    Vertical,
+    /// Swaps the top and bottom halves.
+    Horizontal,
+}
+
+type Permutation = [u8; DIM];
+
+fn identity() -> Permutation {
+    array::from_fn(|x| x as u8)
+}
+
+fn reversed() -> Permutation {
+    array::from_fn(|x| (DIM - 1 - x) as u8)
+}
+
+fn is_permutation(perm: &[usize]) -> bool {
+    let mut seen = [false; DIM];
+    perm.iter()
+        .all(|x| *x < perm.len() && !std::mem::replace(&mut seen[*x], true))
+}
+
+fn compose(first: &Permutation, second: &Permutation) -> Permutation {
+    array::from_fn(|x| second[first[x] as usize])
+}
+
+fn invert(perm: &Permutation) -> Permutation {
+    let mut inverse = [0; DIM];
+    perm.iter()
+        .enumerate()
+        .for_each(|(x, y)| inverse[*y as usize] = x as u8);
+    inverse
+}
+
+fn within_band(band: usize, perm: [usize; BOX]) -> Result<Permutation, TransformationError> {
+    if band >= BOX {
+        return Err(TransformationError::Band);
+    }
+    if !is_permutation(&perm) {
+        return Err(TransformationError::NotAPermutation);
+    }
+    let mut lines = identity();
+    (0..BOX).for_each(|x| lines[band * BOX + x] = (band * BOX + perm[x]) as u8);
+    Ok(lines)
+}
+
+fn of_bands(perm: [usize; BOX]) -> Result<Permutation, TransformationError> {
+    if !is_permutation(&perm) {
+        return Err(TransformationError::NotAPermutation);
+    }
+    Ok(array::from_fn(|x| (perm[x / BOX] * BOX + x % BOX) as u8))
+}
+
+/// Element of the validity-preserving group of a 9x9 sudoku: rows are permuted within bands and
+/// bands are permuted, same for columns and stacks, the grid is optionally transposed and digits
+/// are relabeled.
+///
+/// A cell at row `i` and column `j` moves to row `rows[i]` and column `cols[j]`, then to the
+/// mirrored position across the main diagonal if `transpose` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Transformation {
+    transpose: bool,
+    rows: Permutation,
+    cols: Permutation,
+    digits: Permutation,
+}
+
+impl Default for Transformation {
+    fn default() -> Self {
+        Self {
+            transpose: false,
+            rows: identity(),
+            cols: identity(),
+            digits: identity(),
+        }
+    }
+}
+
+impl Transformation {
+    pub fn identity() -> Self {
+        Default::default()
+    }
+
+    pub fn transpose() -> Self {
+        Self {
+            transpose: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn rotate(rotation: Rotation) -> Self {
+        match rotation {
+            Rotation::Quarter => Self {
+                transpose: true,
+                rows: reversed(),
+                ..Default::default()
+            },
+            Rotation::Half => Self {
+                rows: reversed(),
+                cols: reversed(),
+                ..Default::default()
+            },
+            Rotation::ThreeQuarters => Self {
+                transpose: true,
+                cols: reversed(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn mirror(axis: Axis) -> Self {
+        match axis {
+            Axis::Vertical => Self {
+                cols: reversed(),
+                ..Default::default()
+            },
+            Axis::Horizontal => Self {
+                rows: reversed(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Row `band * 3 + x` moves to row `band * 3 + perm[x]`.
+    pub fn permute_rows(band: usize, perm: [usize; BOX]) -> Result<Self, TransformationError> {
+        Ok(Self {
+            rows: within_band(band, perm)?,
+            ..Default::default()
+        })
+    }
+
+    /// Band `x` moves to band `perm[x]`.
+    pub fn permute_bands(perm: [usize; BOX]) -> Result<Self, TransformationError> {
+        Ok(Self {
+            rows: of_bands(perm)?,
+            ..Default::default()
+        })
+    }
+
+    /// Column `stack * 3 + x` moves to column `stack * 3 + perm[x]`.
+    pub fn permute_cols(stack: usize, perm: [usize; BOX]) -> Result<Self, TransformationError> {
+        Ok(Self {
+            cols: within_band(stack, perm)?,
+            ..Default::default()
+        })
+    }
+
+    /// Stack `x` moves to stack `perm[x]`.
+    pub fn permute_stacks(perm: [usize; BOX]) -> Result<Self, TransformationError> {
+        Ok(Self {
+            cols: of_bands(perm)?,
+            ..Default::default()
+        })
+    }
+
+    /// Digit `d` is replaced with `digits[d - 1]`.
+    pub fn relabel(digits: [Digit; DIM]) -> Result<Self, TransformationError> {
+        let digits = digits.map(usize::from);
+        if !is_permutation(&digits) {
+            return Err(TransformationError::NotAPermutation);
+        }
+        Ok(Self {
+            digits: digits.map(|x| x as u8),
+            ..Default::default()
+        })
+    }
+
+    /// Uniformly random element of the group.
+    pub fn random<R>(rng: &mut R) -> Self
+    where
+        R: Rng + ?Sized,
+    {
+        let mut lines = || {
+            let mut bands = [0, 1, 2];
+            bands.shuffle(rng);
+            let within: [[usize; BOX]; BOX] = array::from_fn(|_| {
+                let mut within = [0, 1, 2];
+                within.shuffle(rng);
+                within
+            });
+            array::from_fn(|x| (bands[x / BOX] * BOX + within[x / BOX][x % BOX]) as u8)
+        };
+        let rows = lines();
+        let cols = lines();
+        let mut digits = identity();
+        digits.shuffle(rng);
+        Self {
+            transpose: rng.random_bool(0.5),
+            rows,
+            cols,
+            digits,
+        }
+    }
+
+    /// Applies `self`, then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let (rows, cols) = if self.transpose {
+            (
+                compose(&self.rows, &next.cols),
+                compose(&self.cols, &next.rows),
+            )
+        } else {
+            (
+                compose(&self.rows, &next.rows),
+                compose(&self.cols, &next.cols),
+            )
+        };
+        Self {
+            transpose: self.transpose != next.transpose,
+            rows,
+            cols,
+            digits: compose(&self.digits, &next.digits),
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        let (rows, cols) = if self.transpose {
+            (invert(&self.cols), invert(&self.rows))
+        } else {
+            (invert(&self.rows), invert(&self.cols))
+        };
+        Self {
+            transpose: self.transpose,
+            rows,
+            cols,
+            digits: invert(&self.digits),
+        }
+    }
+
+    pub fn cell(&self, idx: CellIdx) -> CellIdx {
+        let i = self.rows[usize::from(idx.row)] as usize;
+        let j = self.cols[usize::from(idx.col)] as usize;
+        let (i, j) = if self.transpose { (j, i) } else { (i, j) };
+        (RowIdx::try_from(i).unwrap(), ColIdx::try_from(j).unwrap()).into()
+    }
+
+    pub fn digit(&self, value: Digit) -> Digit {
+        Digit::try_from(self.digits[usize::from(value)]).unwrap()
+    }
+
+    pub fn diff(&self, diff: GridDiff) -> GridDiff {
+        match diff {
+            GridDiff::Set(idx, value) => GridDiff::Set(self.cell(idx), self.digit(value)),
+            GridDiff::Unset(idx) => GridDiff::Unset(self.cell(idx)),
+        }
+    }
+
+    pub fn apply<T, U>(&self, grid: &T) -> U
+    where
+        T: Grid + ?Sized,
+        U: GridMutWithDefault,
+    {
+        U::of_set(
+            grid.iter_set()
+                .map(|(idx, value)| (self.cell(idx), self.digit(value))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Axis, Rotation, Transformation, TransformationError};
+    use crate::format::{read_from_string, write_string, RowMajorAscii};
+    use crate::grid::{ArrGridRowMajor, CellIdx, Digit, GridDiff, GridMut, GridMutWithDefault};
+    use crate::status::{eval_status, SudokuStatus};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use strum::IntoEnumIterator;
+
+    fn solution() -> ArrGridRowMajor {
+        let grid = r#"
+534678912
+672195348
+198342567
+859761423
+426853791
+713924856
+961537284
+287419635
+345286179
+"#
+        .trim();
+        read_from_string(&RowMajorAscii::default(), grid).unwrap()
+    }
+
+    fn apply(t: &Transformation, grid: &ArrGridRowMajor) -> ArrGridRowMajor {
+        t.apply(grid)
+    }
+
+    fn random(seed: u64) -> Transformation {
+        Transformation::random(&mut StdRng::seed_from_u64(seed))
+    }
+
+    #[test]
+    fn test_rotate() {
+        let given = r#"
+12_______
+_________
+_________
+_________
+_________
+_________
+_________
+_________
+_________
+"#
+        .trim();
+        let expected = r#"
+________1
+________2
+_________
+_________
+_________
+_________
+_________
+_________
+_________
+"#
+        .trim();
+        let given: ArrGridRowMajor = read_from_string(&RowMajorAscii::default(), given).unwrap();
+        let rotated = apply(&Transformation::rotate(Rotation::Quarter), &given);
+        assert_eq!(expected, write_string(&RowMajorAscii::default(), &rotated));
+        let quarter = Transformation::rotate(Rotation::Quarter);
+        assert_eq!(
+            Transformation::rotate(Rotation::Half),
+            quarter.then(&quarter)
+        );
+        assert_eq!(
+            Transformation::rotate(Rotation::ThreeQuarters),
+            quarter.inverse()
+        );
+        assert_eq!(
+            Transformation::identity(),
+            quarter.then(&quarter).then(&quarter).then(&quarter)
+        );
+    }
+
+    #[test]
+    fn test_mirror() {
+        let vertical = Transformation::mirror(Axis::Vertical);
+        let horizontal = Transformation::mirror(Axis::Horizontal);
+        assert_eq!(Transformation::identity(), vertical.then(&vertical));
+        assert_eq!(
+            Transformation::rotate(Rotation::Half),
+            vertical.then(&horizontal)
+        );
+        assert_eq!(
+            Transformation::rotate(Rotation::Quarter),
+            Transformation::transpose().then(&vertical)
+        );
+    }
+
+    #[test]
+    fn test_compose() {
+        let grid = solution();
+        (0..16).for_each(|seed| {
+            let (first, second) = (random(seed), random(seed + 16));
+            assert_eq!(
+                apply(&second, &apply(&first, &grid)),
+                apply(&first.then(&second), &grid)
+            );
+        })
+    }
+
+    #[test]
+    fn test_inverse() {
+        let grid = solution();
+        (0..16).for_each(|seed| {
+            let t = random(seed);
+            assert_eq!(Transformation::identity(), t.then(&t.inverse()));
+            assert_eq!(Transformation::identity(), t.inverse().then(&t));
+            assert_eq!(grid, apply(&t.inverse(), &apply(&t, &grid)));
+        })
+    }
+
+    #[test]
+    fn test_preserves_validity() {
+        let grid = solution();
+        let permutations = [
+            Transformation::permute_rows(1, [2, 0, 1]).unwrap(),
+            Transformation::permute_bands([1, 2, 0]).unwrap(),
+            Transformation::permute_cols(2, [1, 0, 2]).unwrap(),
+            Transformation::permute_stacks([2, 1, 0]).unwrap(),
+            Transformation::relabel([
+                Digit::D9,
+                Digit::D8,
+                Digit::D7,
+                Digit::D6,
+                Digit::D5,
+                Digit::D4,
+                Digit::D3,
+                Digit::D2,
+                Digit::D1,
+            ])
+            .unwrap(),
+        ];
+        permutations
+            .iter()
+            .chain([random(0), random(1)].iter())
+            .for_each(|t| {
+                assert_ne!(grid, apply(t, &grid));
+                assert_eq!(
+                    SudokuStatus::Complete,
+                    eval_status(&apply(t, &grid)).unwrap()
+                );
+            })
+    }
+
+    #[test]
+    fn test_diff() {
+        let grid = solution();
+        let t = random(0);
+        let idx = CellIdx::default();
+        let diffs = [GridDiff::Unset(idx), GridDiff::Set(idx, Digit::D9)];
+        diffs.into_iter().for_each(|diff| {
+            let mut expected = grid;
+            expected.apply_diff([diff].into_iter());
+            let actual = ArrGridRowMajor::with_diff(&apply(&t, &grid), [t.diff(diff)].into_iter());
+            assert_eq!(apply(&t, &expected), actual);
+        })
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            Err(TransformationError::Band),
+            Transformation::permute_rows(3, [0, 1, 2])
+        );
+        assert_eq!(
+            Err(TransformationError::NotAPermutation),
+            Transformation::permute_stacks([0, 0, 2])
+        );
+        assert_eq!(
+            Err(TransformationError::NotAPermutation),
+            Transformation::relabel([Digit::D1; 9])
+        );
+        assert!(Digit::iter().all(|d| Transformation::identity().digit(d) == d));
+    }
+}
@@ -4,6 +4,7 @@ use super::Bits9;
 pub struct BoolMatrix9x9(u128);
 
 impl BoolMatrix9x9 {
+    #[allow(dead_code)]
     pub fn clear(&mut self) {
         self.0 = 0;
     }
@@ -20,6 +21,7 @@ impl BoolMatrix9x9 {
         ((self.0 >> (row * 9)) as u16).into()
     }
 
+    #[allow(dead_code)]
     pub fn iter_rows(&self) -> impl Iterator<Item = Bits9> {
         let this = *self;
         (0u8..9u8).map(move |row| this.row(row))
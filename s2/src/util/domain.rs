@@ -17,6 +17,16 @@ impl From<Bits9> for Domain {
 }
 
 impl Domain {
+    /// Domain of the digits whose bits are set in `mask`.
+    pub fn from_mask(mask: u16) -> Self {
+        Bits9::from(!mask).into()
+    }
+
+    /// Digits of the domain as bits.
+    pub fn mask(&self) -> u16 {
+        u16::from(!self.0)
+    }
+
     pub fn size(&self) -> u8 {
         self.0.count_zeros()
     }